@@ -1,16 +1,799 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::Display,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
 pub trait Solver<const N: usize> {
     fn solve(&self);
 }
 
 impl<const N: usize> Solver<N> for (&str, [fn(&str) -> String; N]) {
     fn solve(&self) {
-        let path = format!("src/input/{0}", self.0);
+        let input = read_input(self.0);
+        let args = args();
+        let filter = explain_filter(&args);
+
+        if flag(&args, "bench") {
+            let parts = self.1.iter().enumerate().map(|(i, f)| (i + 1, ("-", *f)));
+
+            return benchmark(self.0, &input, &args, parts.collect());
+        }
+
+        profiled(&args, || {
+            for (i, solve_fn) in self.1.iter().enumerate() {
+                let (answer, events) = explaining(filter.clone(), || {
+                    let _span = span_with("part", || (i + 1).to_string());
+                    solve_fn(&input)
+                });
+
+                println!("part {}: {}", i + 1, answer);
+                print_events(&events);
+            }
+        });
+    }
+}
+
+/// A named way of solving a part, for parts that have more than one.
+pub type Implementation<'a> = (&'a str, fn(&str) -> String);
+
+/// Runs every implementation of every part, printing how long each took, and
+/// exits with a failure when the implementations of a part disagree.
+impl<const N: usize> Solver<N> for (&str, [&[Implementation<'_>]; N]) {
+    fn solve(&self) {
+        let input = read_input(self.0);
+        let args = args();
+        let filter = explain_filter(&args);
+
+        if flag(&args, "bench") {
+            let parts = self.1.iter().enumerate().flat_map(|(i, implementations)| {
+                implementations
+                    .iter()
+                    .map(move |implementation| (i + 1, *implementation))
+            });
+
+            return benchmark(self.0, &input, &args, parts.collect());
+        }
+
+        let agreed = profiled(&args, || {
+            let mut agreed = true;
+
+            for (i, implementations) in self.1.iter().enumerate() {
+                let (comparison, events) = compare(i + 1, implementations, &input, filter.clone());
+                let width = comparison
+                    .runs()
+                    .iter()
+                    .map(|run| run.name.len())
+                    .max()
+                    .unwrap_or(0);
+
+                match comparison {
+                    Comparison::Agreed(runs) => {
+                        println!("part {}: {}", i + 1, runs.first().map_or("", |r| &r.answer));
+
+                        for run in runs.iter() {
+                            println!("  {:width$}  {:?}", run.name, run.elapsed);
+                        }
+                    }
+                    Comparison::Disagreed(runs) => {
+                        agreed = false;
+                        println!("part {}: implementations disagree", i + 1);
+
+                        for run in runs.iter() {
+                            println!("  {:width$}  {}  {:?}", run.name, run.answer, run.elapsed);
+                        }
+                    }
+                }
+
+                print_events(&events);
+            }
+
+            agreed
+        });
+
+        if !agreed {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// What one implementation of a part answered, and how long it took.
+#[derive(Debug)]
+pub struct Run<'a> {
+    pub name: &'a str,
+    pub answer: String,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug)]
+pub enum Comparison<'a> {
+    Agreed(Vec<Run<'a>>),
+    Disagreed(Vec<Run<'a>>),
+}
+
+impl<'a> Comparison<'a> {
+    pub fn runs(&self) -> &[Run<'a>] {
+        match self {
+            Comparison::Agreed(runs) | Comparison::Disagreed(runs) => runs,
+        }
+    }
+}
+
+/// Runs every implementation of `part` on `input` and tells whether they all
+/// gave the same answer. Only the first implementation explains its steps.
+pub fn compare<'a>(
+    part: usize,
+    implementations: &[Implementation<'a>],
+    input: &str,
+    filter: Option<RangeInclusive<usize>>,
+) -> (Comparison<'a>, Vec<Event>) {
+    let mut events = Vec::new();
+    let runs = implementations
+        .iter()
+        .enumerate()
+        .map(|(j, (name, solve_fn))| {
+            let start = Instant::now();
+            let (answer, explained) = explaining(filter.clone().filter(|_| j == 0), || {
+                let _span = span_with("part", || format!("{} {}", part, name));
+                solve_fn(input)
+            });
+            let elapsed = start.elapsed();
+
+            events.extend(explained);
+            Run {
+                name,
+                answer,
+                elapsed,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let comparison = match runs.windows(2).all(|w| w[0].answer == w[1].answer) {
+        true => Comparison::Agreed(runs),
+        false => Comparison::Disagreed(runs),
+    };
+
+    (comparison, events)
+}
+
+pub const HISTORY: &str = "bench/history.txt";
+
+/// One benchmarked part, as stored in the history file.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BenchRecord {
+    pub commit: String,
+    pub machine: String,
+    pub day: String,
+    pub part: usize,
+    pub implementation: String,
+    pub runs: usize,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl BenchRecord {
+    /// Parses a tab-separated history line, times being in seconds.
+    #[allow(dead_code)]
+    pub fn parse(line: &str) -> Option<Self> {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        let [commit, machine, day, part, implementation, runs, mean, stddev] = fields[..] else {
+            return None;
+        };
+
+        Some(Self {
+            commit: commit.to_string(),
+            machine: machine.to_string(),
+            day: day.to_string(),
+            part: part.parse().ok()?,
+            implementation: implementation.to_string(),
+            runs: runs.parse().ok()?,
+            mean: mean.parse().ok()?,
+            stddev: stddev.parse().ok()?,
+        })
+    }
+}
+
+impl Display for BenchRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{:e}\t{:e}",
+            self.commit,
+            self.machine,
+            self.day,
+            self.part,
+            self.implementation,
+            self.runs,
+            self.mean,
+            self.stddev
+        )
+    }
+}
+
+#[allow(dead_code)]
+pub fn read_history(path: &str) -> Vec<BenchRecord> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(BenchRecord::parse)
+        .collect()
+}
+
+/// The short hash of the checked out commit, marked when the tree has
+/// uncommitted changes.
+pub fn current_commit() -> String {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+    };
+
+    match (
+        git(&["rev-parse", "--short", "HEAD"]),
+        git(&["status", "--porcelain", "--untracked-files=no"]),
+    ) {
+        (Some(hash), Some(changes)) if !changes.is_empty() => format!("{}-dirty", hash),
+        (Some(hash), _) => hash,
+        (None, _) => "unknown".to_string(),
+    }
+}
+
+pub fn current_machine() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Times every part `--runs` times, 10 by default, prints the mean and
+/// standard deviation of each and appends them to the history file.
+fn benchmark(name: &str, input: &str, args: &[String], parts: Vec<(usize, Implementation<'_>)>) {
+    let runs: usize = option(args, "runs").map_or(10, |r| r.parse().unwrap());
+    let (commit, machine) = (current_commit(), current_machine());
+    let day = name.trim_end_matches(".txt");
+
+    let records = parts
+        .into_iter()
+        .map(|(part, (implementation, solve_fn))| {
+            let samples = (0..runs.max(2))
+                .map(|_| {
+                    let start = Instant::now();
+                    std::hint::black_box(solve_fn(input));
+
+                    start.elapsed().as_secs_f64()
+                })
+                .collect::<Vec<_>>();
+            let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+            let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>()
+                / (samples.len() - 1) as f64;
+            let record = BenchRecord {
+                commit: commit.clone(),
+                machine: machine.clone(),
+                day: day.to_string(),
+                part,
+                implementation: implementation.to_string(),
+                runs: samples.len(),
+                mean,
+                stddev: variance.sqrt(),
+            };
+
+            println!(
+                "part {} {}: {:?} ± {:?} over {} runs",
+                part,
+                implementation,
+                Duration::from_secs_f64(record.mean),
+                Duration::from_secs_f64(record.stddev),
+                record.runs
+            );
+            record
+        })
+        .collect::<Vec<_>>();
+
+    let mut history = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(HISTORY)
+        .unwrap_or_else(|e| panic!("cannot open {}: {}", HISTORY, e));
+
+    for record in records {
+        std::io::Write::write_all(&mut history, format!("{}\n", record).as_bytes()).unwrap();
+    }
+}
+
+/// Something a part function noticed about one record of the input, such as
+/// a line or a card, shown with `--explain`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Event {
+    pub kind: &'static str,
+    pub record: usize,
+    pub message: String,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.kind, self.record, self.message)
+    }
+}
+
+thread_local! {
+    static EXPLAIN_FILTER: Cell<Option<(usize, usize)>> = const { Cell::new(None) };
+    static EVENTS: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records an event about `record` when explaining is on for it. `message`
+/// is only built in that case, so calling this costs a thread-local read
+/// otherwise.
+pub fn explain(kind: &'static str, record: usize, message: impl FnOnce() -> String) {
+    let Some((first, last)) = EXPLAIN_FILTER.get() else {
+        return;
+    };
+
+    if (first..=last).contains(&record) {
+        EVENTS.with_borrow_mut(|events| {
+            events.push(Event {
+                kind,
+                record,
+                message: message(),
+            })
+        });
+    }
+}
+
+/// Runs `f` with explaining on for the records in `filter`, if any, and
+/// returns what it gave along with the events it recorded. Events are
+/// collected on the calling thread only.
+pub fn explaining<T>(
+    filter: Option<RangeInclusive<usize>>,
+    f: impl FnOnce() -> T,
+) -> (T, Vec<Event>) {
+    let previous = EXPLAIN_FILTER.replace(filter.map(|r| (*r.start(), *r.end())));
+    let outer = EVENTS.take();
+
+    let result = f();
+
+    EXPLAIN_FILTER.set(previous);
+    (result, EVENTS.replace(outer))
+}
+
+/// Reads `--explain` as every record, and `--explain=N` or `--explain=N-M`
+/// as only those records.
+pub fn explain_filter(args: &[String]) -> Option<RangeInclusive<usize>> {
+    if flag(args, "explain") {
+        return Some(0..=usize::MAX);
+    }
+
+    let filter = option(args, "explain")?;
+    let (first, last) = filter.split_once('-').unwrap_or((filter, filter));
+    let parse = |n: &str| {
+        n.trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid --explain filter {}", filter))
+    };
+
+    Some(parse(first)..=parse(last))
+}
+
+fn print_events(events: &[Event]) {
+    for event in events {
+        println!("  {}", event);
+    }
+}
+
+/// A finished span: how long a named phase took, and on which thread.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpanRecord {
+    pub name: &'static str,
+    pub detail: Option<String>,
+    pub thread: u64,
+    /// Time from the start of profiling to the start of the span.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Times a phase until dropped, when profiling is on.
+pub struct Span {
+    open: Option<(&'static str, Option<String>, Instant)>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some((name, detail, start)) = self.open.take() else {
+            return;
+        };
+        let record = SpanRecord {
+            name,
+            detail,
+            thread: THREAD.with(|t| *t),
+            start: start.duration_since(*EPOCH.get_or_init(Instant::now)),
+            duration: start.elapsed(),
+        };
 
-        let input = std::fs::read_to_string(&path)
-            .expect(&format!("input file must be present at {}", &path));
+        SPANS.lock().unwrap().push(record);
+    }
+}
+
+static PROFILING: AtomicBool = AtomicBool::new(false);
+static SPANS: Mutex<Vec<SpanRecord>> = Mutex::new(Vec::new());
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Opens a span named `name`, closed when the returned guard is dropped.
+/// When profiling is off this costs an atomic read.
+pub fn span(name: &'static str) -> Span {
+    span_with(name, String::new)
+}
+
+/// Like `span`, with a detail such as a stage name that is only built when
+/// profiling is on.
+pub fn span_with(name: &'static str, detail: impl FnOnce() -> String) -> Span {
+    match PROFILING.load(Ordering::Relaxed) {
+        true => Span {
+            open: Some((
+                name,
+                Some(detail()).filter(|d| !d.is_empty()),
+                Instant::now(),
+            )),
+        },
+        false => Span { open: None },
+    }
+}
+
+/// Runs `f` with profiling on and returns what it gave along with every span
+/// closed meanwhile, on any thread.
+pub fn profiling<T>(f: impl FnOnce() -> T) -> (T, Vec<SpanRecord>) {
+    EPOCH.get_or_init(Instant::now);
+    PROFILING.store(true, Ordering::Relaxed);
+
+    let result = f();
+
+    PROFILING.store(false, Ordering::Relaxed);
+    (result, std::mem::take(&mut *SPANS.lock().unwrap()))
+}
+
+/// Runs `f`, profiling it into the Chrome trace file given by `--profile`
+/// when there is one.
+fn profiled<T>(args: &[String], f: impl FnOnce() -> T) -> T {
+    let Some(path) = option(args, "profile") else {
+        return f();
+    };
+    let (result, spans) = profiling(f);
+
+    std::fs::write(path, chrome_trace(&spans))
+        .unwrap_or_else(|e| panic!("cannot write profile to {}: {}", path, e));
+    result
+}
+
+/// Renders spans in the Chrome trace event format, which chrome://tracing and
+/// Perfetto open.
+pub fn chrome_trace(spans: &[SpanRecord]) -> String {
+    let events = spans
+        .iter()
+        .map(|s| {
+            let name = match &s.detail {
+                Some(detail) => format!("{} {}", s.name, detail),
+                None => s.name.to_string(),
+            };
+
+            format!(
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}}",
+                escape_json(&name),
+                escape_json(s.name),
+                s.start.as_secs_f64() * 1e6,
+                s.duration.as_secs_f64() * 1e6,
+                s.thread
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"))
+}
+
+/// Escapes a string for use inside a double-quoted JSON string.
+pub fn escape_json(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            c if c.is_control() => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Escapes text for use within XML or HTML elements.
+#[allow(dead_code)]
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A small deterministic generator for building inputs, so that the same
+/// seed always gives the same input.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    #[allow(dead_code)]
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+/// A way of growing a day's input, generating an input of a given size.
+pub struct Dimension {
+    pub name: &'static str,
+    pub generate: fn(usize, &mut Rng) -> String,
+    /// The smallest size measured, doubled at every step.
+    pub start: usize,
+}
+
+const SCALING_BASELINE: &str = "bench/scaling.txt";
+
+/// How far above its recorded exponent a fit may land before it counts as a
+/// worse growth. Timing noise moves fits by a quarter or so, while going from
+/// linear to quadratic adds one.
+const SCALING_TOLERANCE: f64 = 0.5;
+
+/// Times every part on inputs of doubling size along each dimension and fits
+/// the exponent `k` of `time ~ size^k`. Exits with a failure when `k` exceeds
+/// the one recorded in the baseline by more than `--tolerance`, 0.5 unless
+/// given. `--record` stores the measured exponents as the new baseline
+/// instead.
+pub fn scaling<const N: usize>(
+    day: &str,
+    dimensions: &[Dimension],
+    parts: [fn(&str) -> String; N],
+) {
+    let args = args();
+    let steps = option(&args, "steps").map_or(5, |s| s.parse().unwrap());
+    let runs = option(&args, "runs").map_or(3, |r| r.parse().unwrap());
+    let tolerance = option(&args, "tolerance").map_or(SCALING_TOLERANCE, |t| t.parse().unwrap());
+    let mut baseline = read_baseline(SCALING_BASELINE);
+    let mut worse = false;
+
+    if steps < 2 {
+        println!("fitting an exponent takes at least 2 steps");
+        std::process::exit(1);
+    }
+
+    for dimension in dimensions {
+        let inputs = (0..steps)
+            .map(|k| dimension.start << k)
+            .map(|size| (size, (dimension.generate)(size, &mut Rng::new(size as u64))))
+            .collect::<Vec<_>>();
+
+        for (i, part) in parts.iter().enumerate() {
+            let points = inputs
+                .iter()
+                .map(|(size, input)| (*size as f64, best_of(runs, || part(input))))
+                .collect::<Vec<_>>();
+            let exponent = fit_exponent(&points);
+            let key = format!("{} {} part-{}", day, dimension.name, i + 1);
 
-        for (i, solve_fn) in self.1.iter().enumerate() {
-            println!("part {}: {}", i + 1, solve_fn(&input))
+            print!("{}: {:.2}", key, exponent);
+
+            match baseline.get(&key) {
+                Some(recorded) if is_worse(exponent, *recorded, tolerance) => {
+                    worse = true;
+                    println!(", worse than baseline {:.2}", recorded);
+                }
+                Some(recorded) => println!(", baseline {:.2}", recorded),
+                None => println!(", no baseline"),
+            }
+
+            baseline.insert(key, exponent);
         }
     }
+
+    if flag(&args, "record") {
+        write_baseline(SCALING_BASELINE, &baseline);
+    } else if worse {
+        std::process::exit(1);
+    }
+}
+
+/// The fastest of `runs` runs of `f`, in seconds.
+fn best_of(runs: usize, f: impl Fn() -> String) -> f64 {
+    (0..runs.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            std::hint::black_box(f());
+
+            start.elapsed().as_secs_f64()
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Tells whether a fitted exponent grows faster than the recorded one, by more
+/// than `tolerance`.
+fn is_worse(exponent: f64, recorded: f64, tolerance: f64) -> bool {
+    exponent > recorded + tolerance
+}
+
+/// The least squares slope of `ln(time)` against `ln(size)`.
+fn fit_exponent(points: &[(f64, f64)]) -> f64 {
+    let logs = points
+        .iter()
+        .map(|(size, time)| (size.ln(), time.max(1e-9).ln()))
+        .collect::<Vec<_>>();
+    let n = logs.len() as f64;
+    let mean_x = logs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let covariance = logs
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    let variance = logs.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f64>();
+
+    covariance / variance
+}
+
+fn read_baseline(path: &str) -> BTreeMap<String, f64> {
+    parse_baseline(&std::fs::read_to_string(path).unwrap_or_default())
+}
+
+fn write_baseline(path: &str, baseline: &BTreeMap<String, f64>) {
+    std::fs::write(path, format_baseline(baseline))
+        .unwrap_or_else(|e| panic!("cannot write {}: {}", path, e));
+}
+
+/// Reads `<key> <exponent>` lines, the key being everything before the last
+/// space.
+fn parse_baseline(text: &str) -> BTreeMap<String, f64> {
+    text.lines()
+        .filter_map(|l| {
+            let (key, exponent) = l.rsplit_once(' ')?;
+
+            Some((key.to_string(), exponent.parse().ok()?))
+        })
+        .collect()
+}
+
+fn format_baseline(baseline: &BTreeMap<String, f64>) -> String {
+    baseline
+        .iter()
+        .map(|(key, exponent)| format!("{} {:.2}\n", key, exponent))
+        .collect()
+}
+
+pub fn read_input(name: &str) -> String {
+    let path = format!("src/input/{0}", name);
+
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("input file must be present at {}", &path))
+}
+
+pub fn args() -> Vec<String> {
+    std::env::args().skip(1).collect()
+}
+
+/// Finds the value of a `--name=value` argument.
+pub fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|a| a.strip_prefix("--")?.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// Tells whether a bare `--name` argument was passed.
+pub fn flag(args: &[String], name: &str) -> bool {
+    args.iter()
+        .any(|a| a.strip_prefix("--").is_some_and(|a| a == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn implementation(name: &str) -> Implementation<'_> {
+        let solve_fn: fn(&str) -> String = match name {
+            "bytes" => |input| input.len().to_string(),
+            "chars" => |input| input.chars().count().to_string(),
+            _ => |input| input.chars().take(1).collect(),
+        };
+
+        (name, solve_fn)
+    }
+
+    #[rstest]
+    #[case(&["bytes", "chars"], true, &["2", "2"])]
+    #[case(&["bytes"], true, &["2"])]
+    #[case(&["bytes", "chars", "first"], false, &["2", "2", "4"])]
+    fn compare_test(#[case] names: &[&str], #[case] agreed: bool, #[case] answers: &[&str]) {
+        let implementations = names.iter().map(|n| implementation(n)).collect::<Vec<_>>();
+        let (comparison, events) = compare(1, &implementations, "42", None);
+
+        assert_eq!(matches!(comparison, Comparison::Agreed(_)), agreed);
+        assert_eq!(
+            comparison
+                .runs()
+                .iter()
+                .map(|run| (run.name, run.answer.as_str()))
+                .collect::<Vec<_>>(),
+            names
+                .iter()
+                .copied()
+                .zip(answers.iter().copied())
+                .collect::<Vec<_>>()
+        );
+        assert!(events.is_empty());
+    }
+
+    #[rstest]
+    #[case(|_| 3.0, 0.0)]
+    #[case(|n| 2.0 * n, 1.0)]
+    #[case(|n| n * n / 1000.0, 2.0)]
+    fn fit_exponent_test(#[case] time: fn(f64) -> f64, #[case] expected: f64) {
+        let points = (0..5)
+            .map(|k| (1000 << k) as f64)
+            .map(|size| (size, time(size)))
+            .collect::<Vec<_>>();
+
+        assert!((fit_exponent(&points) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn baseline_round_trip_test() {
+        let baseline = BTreeMap::from([
+            ("day01 lines part-1".to_string(), 1.02),
+            ("day05 seeds part-2".to_string(), 0.0),
+        ]);
+        let text = format_baseline(&baseline);
+
+        assert_eq!(text, "day01 lines part-1 1.02\nday05 seeds part-2 0.00\n");
+        assert_eq!(parse_baseline(&text), baseline);
+        assert_eq!(
+            parse_baseline("day01 lines part-1 fast\nnospace\n"),
+            BTreeMap::new()
+        );
+    }
+
+    #[rstest]
+    #[case(1.0, 1.0, 0.5, false)]
+    #[case(1.4, 1.0, 0.5, false)]
+    #[case(1.6, 1.0, 0.5, true)]
+    #[case(2.0, 1.0, 0.5, true)]
+    #[case(0.2, 1.0, 0.5, false)]
+    #[case(1.2, 1.0, 0.1, true)]
+    fn is_worse_test(
+        #[case] exponent: f64,
+        #[case] recorded: f64,
+        #[case] tolerance: f64,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(is_worse(exponent, recorded, tolerance), expected);
+    }
 }
@@ -0,0 +1,219 @@
+// Only the argument and history helpers of the runner are used here.
+#[allow(dead_code)]
+mod aoc;
+
+use aoc::BenchRecord;
+use itertools::Itertools;
+use std::{collections::BTreeMap, time::Duration};
+
+/// Welch's t statistic above which a difference is taken to be more than
+/// noise, about 95% confidence for the run counts we use.
+const SIGNIFICANT_T: f64 = 2.0;
+
+fn main() {
+    let args = aoc::args();
+
+    match args.first().map(String::as_str) {
+        Some("compare") => {
+            let machine = aoc::option(&args, "machine").map_or_else(aoc::current_machine, String::from);
+            let threshold = aoc::option(&args, "threshold").map_or(5.0, |t| t.parse().unwrap());
+            let history = aoc::read_history(aoc::HISTORY)
+                .into_iter()
+                .filter(|r| r.machine == machine)
+                .collect_vec();
+
+            let commits = history.iter().map(|r| r.commit.as_str()).unique().collect_vec();
+            let candidate = aoc::option(&args, "commit").or(commits.last().copied());
+            let baseline = aoc::option(&args, "baseline").or_else(|| {
+                let i = commits.iter().position(|c| Some(*c) == candidate)?;
+                commits.get(i.checked_sub(1)?).copied()
+            });
+
+            let (Some(baseline), Some(candidate)) = (baseline, candidate) else {
+                println!("need benchmarks of two commits on {} to compare", machine);
+                std::process::exit(1);
+            };
+
+            println!("{} against {} on {}", candidate, baseline, machine);
+
+            let comparisons = compare(&history, baseline, candidate);
+            let mut regressed = false;
+
+            for c in comparisons.iter() {
+                let verdict = c.verdict(threshold);
+                regressed |= verdict == Verdict::Regressed;
+
+                println!(
+                    "{} part {} {:<10} {:>12} {:>12} {:>+8.1}% t={:>6.2}  {}",
+                    c.day,
+                    c.part,
+                    c.implementation,
+                    format!("{:.2?}", Duration::from_secs_f64(c.baseline.mean)),
+                    format!("{:.2?}", Duration::from_secs_f64(c.candidate.mean)),
+                    c.delta(),
+                    c.t(),
+                    verdict
+                );
+            }
+
+            if regressed {
+                std::process::exit(1);
+            }
+        }
+        _ => println!(
+            "usage: bench compare [--baseline=COMMIT] [--commit=COMMIT] [--machine=NAME] [--threshold=PERCENT]"
+        ),
+    }
+}
+
+struct Comparison {
+    day: String,
+    part: usize,
+    implementation: String,
+    baseline: BenchRecord,
+    candidate: BenchRecord,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Verdict {
+    Noise,
+    Faster,
+    Slower,
+    Regressed,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Noise => write!(f, "within noise"),
+            Verdict::Faster => write!(f, "faster"),
+            Verdict::Slower => write!(f, "slower"),
+            Verdict::Regressed => write!(f, "REGRESSED"),
+        }
+    }
+}
+
+impl Comparison {
+    /// How much slower the candidate is, in percent of the baseline.
+    fn delta(&self) -> f64 {
+        (self.candidate.mean - self.baseline.mean) / self.baseline.mean * 100.0
+    }
+
+    /// Welch's t statistic of the difference between both means.
+    fn t(&self) -> f64 {
+        let error = |r: &BenchRecord| r.stddev.powi(2) / r.runs as f64;
+        let spread = (error(&self.baseline) + error(&self.candidate)).sqrt();
+
+        match spread > 0.0 {
+            true => (self.candidate.mean - self.baseline.mean) / spread,
+            false => 0.0,
+        }
+    }
+
+    /// A part regresses when it is significantly slower by more than
+    /// `threshold` percent.
+    fn verdict(&self, threshold: f64) -> Verdict {
+        match (self.t().abs() > SIGNIFICANT_T, self.delta()) {
+            (false, _) => Verdict::Noise,
+            (true, delta) if delta < 0.0 => Verdict::Faster,
+            (true, delta) if delta > threshold => Verdict::Regressed,
+            (true, _) => Verdict::Slower,
+        }
+    }
+}
+
+/// Pairs the latest record of every part benchmarked on both commits.
+fn compare(history: &[BenchRecord], baseline: &str, candidate: &str) -> Vec<Comparison> {
+    let latest = |commit: &str| {
+        history
+            .iter()
+            .filter(|r| r.commit == commit)
+            .map(|r| ((r.day.clone(), r.part, r.implementation.clone()), r.clone()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let baseline = latest(baseline);
+
+    latest(candidate)
+        .into_iter()
+        .filter_map(|((day, part, implementation), candidate)| {
+            Some(Comparison {
+                baseline: baseline
+                    .get(&(day.clone(), part, implementation.clone()))?
+                    .clone(),
+                day,
+                part,
+                implementation,
+                candidate,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn record(commit: &str, day: &str, part: usize, mean: f64, stddev: f64) -> BenchRecord {
+        BenchRecord {
+            commit: commit.to_string(),
+            machine: "box".to_string(),
+            day: day.to_string(),
+            part,
+            implementation: "-".to_string(),
+            runs: 10,
+            mean,
+            stddev,
+        }
+    }
+
+    #[test]
+    fn record_round_trip_test() {
+        let record = record("abc1234", "day05", 2, 0.00123, 0.0000456);
+
+        assert_eq!(BenchRecord::parse(&record.to_string()), Some(record));
+        assert_eq!(BenchRecord::parse("abc\tbox\tday01"), None);
+    }
+
+    #[rstest]
+    #[case(1.0, 0.01, 1.2, 0.01, Verdict::Regressed)]
+    #[case(1.0, 0.01, 1.02, 0.01, Verdict::Slower)]
+    #[case(1.0, 0.01, 0.8, 0.01, Verdict::Faster)]
+    #[case(1.0, 0.3, 1.2, 0.3, Verdict::Noise)]
+    #[case(1.0, 0.0, 1.0, 0.0, Verdict::Noise)]
+    fn verdict_test(
+        #[case] baseline_mean: f64,
+        #[case] baseline_stddev: f64,
+        #[case] candidate_mean: f64,
+        #[case] candidate_stddev: f64,
+        #[case] expected: Verdict,
+    ) {
+        let history = [
+            record("old", "day01", 1, baseline_mean, baseline_stddev),
+            record("new", "day01", 1, candidate_mean, candidate_stddev),
+        ];
+        let comparisons = compare(&history, "old", "new");
+
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].verdict(5.0), expected);
+    }
+
+    #[test]
+    fn compare_test() {
+        let history = [
+            record("old", "day01", 1, 9.0, 0.1),
+            record("old", "day01", 1, 1.0, 0.1),
+            record("old", "day02", 1, 1.0, 0.1),
+            record("new", "day01", 1, 2.0, 0.1),
+            record("new", "day03", 1, 2.0, 0.1),
+        ];
+
+        assert_eq!(
+            compare(&history, "old", "new")
+                .iter()
+                .map(|c| (c.day.as_str(), c.delta()))
+                .collect_vec(),
+            [("day01", 100.0)]
+        );
+    }
+}
@@ -1,9 +1,30 @@
 mod aoc;
 
-use aoc::Solver;
+use aoc::{Dimension, Rng, Solver};
 
 fn main() {
-    ("day01.txt", [part_1, part_2]).solve();
+    match aoc::args().first().map(String::as_str) {
+        Some("scaling") => aoc::scaling(
+            "day01",
+            &[Dimension {
+                name: "lines",
+                generate: generate_document,
+                start: 1000,
+            }],
+            [part_1, part_2],
+        ),
+        _ => (
+            "day01.txt",
+            [
+                &[
+                    ("extract", part_1 as fn(&str) -> String),
+                    ("scan", part_1_scan),
+                ][..],
+                &[("extract", part_2), ("scan", part_2_scan)],
+            ],
+        )
+            .solve(),
+    }
 }
 
 fn part_1(input: &str) -> String {
@@ -15,14 +36,64 @@ fn part_2(input: &str) -> String {
 }
 
 fn solve_with_extractor(input: &str, extractor: fn(&str) -> Vec<u32>) -> String {
+    let _span = aoc::span("extract_numbers");
+
     input
         .lines()
         .map(extractor)
-        .map(|numbers| numbers.first().unwrap() * 10 + numbers.last().unwrap())
+        .enumerate()
+        .map(|(i, numbers)| {
+            let (first, last) = (numbers.first().unwrap(), numbers.last().unwrap());
+            aoc::explain("line", i + 1, || format!("→ {}, {}", first, last));
+
+            first * 10 + last
+        })
+        .sum::<u32>()
+        .to_string()
+}
+
+fn part_1_scan(input: &str) -> String {
+    solve_with_scanner(input, digit_at_1)
+}
+
+fn part_2_scan(input: &str) -> String {
+    solve_with_scanner(input, digit_at_2)
+}
+
+/// Looks for the first and last digits from each end of the line instead of
+/// extracting every digit in between.
+fn solve_with_scanner(input: &str, digit_at: fn(&str) -> Option<u32>) -> String {
+    let _span = aoc::span("scan");
+
+    input
+        .lines()
+        .map(|line| {
+            let first = (0..line.len()).find_map(|i| digit_at(&line[i..]));
+            let last = (0..line.len()).rev().find_map(|i| digit_at(&line[i..]));
+
+            first.unwrap() * 10 + last.unwrap()
+        })
         .sum::<u32>()
         .to_string()
 }
 
+fn digit_at_1(rest: &str) -> Option<u32> {
+    rest.chars().next()?.to_digit(10)
+}
+
+fn digit_at_2(rest: &str) -> Option<u32> {
+    const WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    digit_at_1(rest).or_else(|| {
+        WORDS
+            .iter()
+            .position(|w| rest.starts_with(w))
+            .map(|i| i as u32 + 1)
+    })
+}
+
 fn extract_numbers_1(line: &str) -> Vec<u32> {
     line.chars().filter_map(|c| c.to_digit(10)).collect()
 }
@@ -44,7 +115,7 @@ fn extract_numbers_2(line: &str) -> Vec<u32> {
 
     for offset in 0..line.len() {
         let rest = &line[offset..];
-        if let Some(number) = rest.chars().nth(0).and_then(|c| c.to_digit(10)) {
+        if let Some(number) = rest.chars().next().and_then(|c| c.to_digit(10)) {
             numbers.push(number);
             continue;
         } else {
@@ -59,6 +130,23 @@ fn extract_numbers_2(line: &str) -> Vec<u32> {
     numbers
 }
 
+/// A calibration document of `lines` lines, each with at least one digit.
+fn generate_document(lines: usize, rng: &mut Rng) -> String {
+    const CHARACTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz123456789";
+
+    (0..lines)
+        .map(|_| {
+            let mut line = (0..5 + rng.below(30))
+                .map(|_| CHARACTERS[rng.below(CHARACTERS.len() as u64) as usize] as char)
+                .collect::<String>();
+            let at = rng.below(line.len() as u64) as usize;
+            line.insert(at, char::from_digit(1 + rng.below(9) as u32, 10).unwrap());
+
+            line + "\n"
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +172,41 @@ mod tests {
     fn extract_numbers_2_test(#[case] input: &str, #[case] expected: Vec<u32>) {
         assert_eq!(expected, extract_numbers_2(input))
     }
+
+    #[rstest]
+    #[case(
+        "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet",
+        part_1,
+        part_1_scan,
+        "142"
+    )]
+    #[case(
+        "two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\nzoneight234\n7pqrstsixteen",
+        part_2,
+        part_2_scan,
+        "281"
+    )]
+    fn implementations_agree_test(
+        #[case] input: &str,
+        #[case] extract: fn(&str) -> String,
+        #[case] scan: fn(&str) -> String,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(extract(input), expected);
+        assert_eq!(scan(input), expected);
+    }
+
+    #[test]
+    fn explain_test() {
+        let (answer, events) = aoc::explaining(Some(2..=3), || {
+            part_2("two1nine\neightwothree\nabcone2threexyz\nxtwone3four")
+        });
+
+        assert_eq!(answer, "149");
+        assert_eq!(
+            events.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            ["line 2 → 8, 3", "line 3 → 1, 3"]
+        );
+        assert!(aoc::explaining(None, || part_2("two1nine")).1.is_empty());
+    }
 }
@@ -1,16 +1,87 @@
 mod aoc;
 
-use aoc::Solver;
+use aoc::{Dimension, Rng, Solver};
+use itertools::Itertools;
+use std::{collections::BTreeMap, fmt::Display};
 
 fn main() {
-    ("day02.txt", [part_1, part_2]).solve();
+    let args = aoc::args();
+
+    match args.iter().map(String::as_str).collect_vec().as_slice() {
+        ["feasible", quantities @ ..] => {
+            let bag = match parse_quantities(quantities).as_deref() {
+                Some(&[red, green, blue]) => Round {
+                    id: 0,
+                    red,
+                    green,
+                    blue,
+                },
+                _ => usage("feasible RED GREEN BLUE"),
+            };
+
+            for (id, feasibility) in feasible_games(&parse_games(&input()), &bag) {
+                println!("game {}: {}", id, feasibility);
+            }
+        }
+        ["smallest-bag", quantities @ ..] => {
+            let k = match parse_quantities(quantities).as_deref() {
+                Some(&[k]) => k,
+                _ => usage("smallest-bag K"),
+            };
+
+            match smallest_bag(&parse_games(&input()), k) {
+                Some(bag) => println!("{} red, {} green, {} blue", bag.red, bag.green, bag.blue),
+                None => println!("not enough games"),
+            }
+        }
+        ["distribution"] => {
+            for (color, histogram) in fewest_distribution(&parse_games(&input())) {
+                println!("{}:", color);
+
+                for (quantity, games) in histogram {
+                    println!("{:>4} {}", quantity, "#".repeat(games));
+                }
+            }
+        }
+        ["scaling", ..] => aoc::scaling(
+            "day02",
+            &[Dimension {
+                name: "games",
+                generate: generate_games,
+                start: 1000,
+            }],
+            [part_1, part_2],
+        ),
+        _ => ("day02.txt", [part_1, part_2]).solve(),
+    }
+}
+
+fn input() -> String {
+    aoc::read_input("day02.txt")
+}
+
+fn parse_quantities(quantities: &[&str]) -> Option<Vec<usize>> {
+    quantities.iter().map(|q| q.parse().ok()).collect()
+}
+
+fn usage(subcommand: &str) -> ! {
+    println!("usage: day02 {}", subcommand);
+    std::process::exit(1);
 }
 
 fn part_1(input: &str) -> String {
     input
         .lines()
         .map(parse_line)
-        .filter(check_feasibility)
+        .filter(|rs| {
+            let feasible = check_feasibility(rs);
+            aoc::explain("game", rs.first().unwrap().id, || match feasible {
+                true => "is feasible".to_string(),
+                false => "is not feasible".to_string(),
+            });
+
+            feasible
+        })
         .map(|rs| rs.first().unwrap().id)
         .sum::<usize>()
         .to_string()
@@ -20,23 +91,34 @@ fn part_2(input: &str) -> String {
     input
         .lines()
         .map(parse_line)
-        .map(|rs| check_fewest(&rs))
+        .map(|rs| {
+            let fewest = check_fewest(&rs);
+            aoc::explain("game", rs.first().unwrap().id, || {
+                format!(
+                    "needs {} red, {} green, {} blue",
+                    fewest.red, fewest.green, fewest.blue
+                )
+            });
+
+            fewest
+        })
         .map(|r| r.red * r.green * r.blue)
         .sum::<usize>()
         .to_string()
 }
 
-fn check_feasibility(rounds: &Vec<Round>) -> bool {
-    const RED_MAX: usize = 12;
-    const GREEN_MAX: usize = 13;
-    const BLUE_MAX: usize = 14;
+fn check_feasibility(rounds: &[Round]) -> bool {
+    const BAG: Round = Round {
+        id: 0,
+        red: 12,
+        green: 13,
+        blue: 14,
+    };
 
-    rounds
-        .iter()
-        .all(|r| r.red <= RED_MAX && r.green <= GREEN_MAX && r.blue <= BLUE_MAX)
+    blocking_colors(rounds, &BAG).is_empty()
 }
 
-fn check_fewest(rounds: &Vec<Round>) -> Round {
+fn check_fewest(rounds: &[Round]) -> Round {
     rounds.iter().fold(Default::default(), |f, e| Round {
         id: 0,
         red: f.red.max(e.red),
@@ -45,7 +127,97 @@ fn check_fewest(rounds: &Vec<Round>) -> Round {
     })
 }
 
+fn blocking_colors(rounds: &[Round], bag: &Round) -> Vec<Color> {
+    let fewest = check_fewest(rounds);
+
+    Color::ALL
+        .into_iter()
+        .filter(|c| fewest.get(*c) > bag.get(*c))
+        .collect()
+}
+
+fn feasible_games(games: &[Vec<Round>], bag: &Round) -> Vec<(usize, Feasibility)> {
+    games
+        .iter()
+        .map(|rs| {
+            let feasibility = match blocking_colors(rs, bag).as_slice() {
+                [] => Feasibility::Feasible,
+                [color] => Feasibility::BlockedBy(*color),
+                _ => Feasibility::BlockedByMany,
+            };
+
+            (rs.first().unwrap().id, feasibility)
+        })
+        .collect()
+}
+
+/// Finds the bag with the fewest cubes in total under which at least `k` games
+/// are feasible, breaking ties by the lowest power.
+fn smallest_bag(games: &[Vec<Round>], k: usize) -> Option<Round> {
+    if k == 0 {
+        return Some(Default::default());
+    }
+
+    let fewest = games.iter().map(|rs| check_fewest(rs)).collect_vec();
+    let candidates = |color| fewest.iter().map(|f| f.get(color)).sorted().dedup();
+
+    candidates(Color::Red)
+        .cartesian_product(candidates(Color::Green).collect_vec())
+        .filter_map(|(red, green)| {
+            let blue = fewest
+                .iter()
+                .filter(|f| f.red <= red && f.green <= green)
+                .map(|f| f.blue)
+                .sorted()
+                .nth(k - 1)?;
+
+            Some(Round {
+                id: 0,
+                red,
+                green,
+                blue,
+            })
+        })
+        .min_by_key(|b| (b.red + b.green + b.blue, b.red * b.green * b.blue))
+}
+
+/// Counts, for every color, how many games need exactly each quantity of it
+/// at the fewest.
+fn fewest_distribution(games: &[Vec<Round>]) -> Vec<(Color, BTreeMap<usize, usize>)> {
+    let fewest = games.iter().map(|rs| check_fewest(rs)).collect_vec();
+
+    Color::ALL
+        .into_iter()
+        .map(|color| (color, fewest.iter().map(|f| f.get(color)).counts()))
+        .map(|(color, counts)| (color, counts.into_iter().collect()))
+        .collect()
+}
+
+fn generate_games(games: usize, rng: &mut Rng) -> String {
+    (1..=games)
+        .map(|id| {
+            let rounds = (0..1 + rng.below(6)).map(|_| {
+                let mut colors = Color::ALL.to_vec();
+                rng.shuffle(&mut colors);
+                colors.truncate(1 + rng.below(3) as usize);
+
+                colors
+                    .iter()
+                    .map(|c| format!("{} {}", 1 + rng.below(20), c))
+                    .join(", ")
+            });
+
+            format!("Game {}: {}\n", id, rounds.collect_vec().join("; "))
+        })
+        .collect()
+}
+
+fn parse_games(input: &str) -> Vec<Vec<Round>> {
+    input.lines().map(parse_line).collect()
+}
+
 fn parse_line(line: &str) -> Vec<Round> {
+    let _span = aoc::span("parse_line");
     let (header, values) = line.split_once(':').unwrap();
 
     let id = header.split_once(' ').unwrap().1.parse::<usize>().unwrap();
@@ -53,9 +225,9 @@ fn parse_line(line: &str) -> Vec<Round> {
     values
         .split(';')
         .map(|l: &str| -> Round {
-            let mut red = 0 as usize;
-            let mut green = 0 as usize;
-            let mut blue = 0 as usize;
+            let mut red = 0;
+            let mut green = 0;
+            let mut blue = 0;
 
             for (quantity_str, color) in l.split(',').filter_map(|s| s.trim().split_once(' ')) {
                 if let Ok(quantity) = quantity_str.parse::<usize>() {
@@ -86,7 +258,7 @@ fn parse_line(line: &str) -> Vec<Round> {
         .collect()
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
 struct Round {
     id: usize,
     red: usize,
@@ -94,11 +266,65 @@ struct Round {
     blue: usize,
 }
 
+impl Round {
+    fn get(&self, color: Color) -> usize {
+        match color {
+            Color::Red => self.red,
+            Color::Green => self.green,
+            Color::Blue => self.blue,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Color {
+    const ALL: [Color; 3] = [Color::Red, Color::Green, Color::Blue];
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Color::Red => write!(f, "red"),
+            Color::Green => write!(f, "green"),
+            Color::Blue => write!(f, "blue"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Feasibility {
+    Feasible,
+    BlockedBy(Color),
+    BlockedByMany,
+}
+
+impl Display for Feasibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feasibility::Feasible => write!(f, "feasible"),
+            Feasibility::BlockedBy(color) => write!(f, "blocked by {}", color),
+            Feasibility::BlockedByMany => write!(f, "blocked by more than one color"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rstest::rstest;
 
+    const EXAMPLE_GAMES: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
     #[rstest]
     #[case("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green", true)]
     #[case(
@@ -117,4 +343,52 @@ mod tests {
     fn extract_numbers_1_test(#[case] input: &str, #[case] expected: bool) {
         assert_eq!(expected, check_feasibility(&parse_line(input)))
     }
+
+    #[rstest]
+    #[case((12, 13, 14), [Feasibility::Feasible, Feasibility::Feasible, Feasibility::BlockedBy(Color::Red), Feasibility::BlockedByMany, Feasibility::Feasible].into())]
+    #[case((20, 13, 6), [Feasibility::Feasible, Feasibility::Feasible, Feasibility::Feasible, Feasibility::BlockedBy(Color::Blue), Feasibility::Feasible].into())]
+    #[case((0, 0, 0), [Feasibility::BlockedByMany; 5].into())]
+    fn feasible_games_test(#[case] bag: (usize, usize, usize), #[case] expected: Vec<Feasibility>) {
+        let bag = Round {
+            id: 0,
+            red: bag.0,
+            green: bag.1,
+            blue: bag.2,
+        };
+
+        assert_eq!(
+            feasible_games(&parse_games(EXAMPLE_GAMES), &bag),
+            (1..=5).zip(expected).collect_vec()
+        );
+    }
+
+    #[rstest]
+    #[case(0, Some((0, 0, 0)))]
+    #[case(1, Some((1, 3, 4)))]
+    #[case(3, Some((6, 3, 6)))]
+    #[case(5, Some((20, 13, 15)))]
+    #[case(6, None)]
+    fn smallest_bag_test(#[case] k: usize, #[case] expected: Option<(usize, usize, usize)>) {
+        assert_eq!(
+            smallest_bag(&parse_games(EXAMPLE_GAMES), k).map(|b| (b.red, b.green, b.blue)),
+            expected
+        );
+    }
+
+    #[test]
+    fn fewest_distribution_test() {
+        let distribution = fewest_distribution(&parse_games(EXAMPLE_GAMES));
+
+        assert_eq!(
+            distribution
+                .into_iter()
+                .map(|(c, h)| (c, h.into_iter().collect_vec()))
+                .collect_vec(),
+            vec![
+                (Color::Red, vec![(1, 1), (4, 1), (6, 1), (14, 1), (20, 1)]),
+                (Color::Green, vec![(2, 1), (3, 3), (13, 1)]),
+                (Color::Blue, vec![(2, 1), (4, 1), (6, 2), (15, 1)]),
+            ]
+        );
+    }
 }
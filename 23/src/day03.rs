@@ -1,126 +1,570 @@
 mod aoc;
-use aoc::Solver;
+#[path = "day03/graph.rs"]
+mod graph;
+mod grid;
+#[path = "day03/render.rs"]
+mod render;
+
+use aoc::{Dimension, Rng, Solver};
+use grid::{Grid, Position};
 use itertools::Itertools;
-use std::{num::Wrapping, ops::Not};
+use std::{collections::BTreeMap, ops::Not};
+
+const SYMBOLS: [char; 10] = ['+', '%', '*', '$', '#', '/', '=', '@', '-', '&'];
 
 fn main() {
-    ("day03.txt", [part_1, part_2]).solve();
+    let args = aoc::args();
+
+    let symbols =
+        aoc::option(&args, "symbols").map_or(SymbolClass::Set(SYMBOLS.into()), SymbolClass::parse);
+    let adjacency =
+        aoc::option(&args, "adjacency").map_or(Adjacency::Eight, |a| Adjacency::parse(a).unwrap());
+
+    match args.first().map(String::as_str) {
+        Some("check") => match parse_schematic(&input(), Shape::Strict) {
+            Ok(grid) => println!("{} lines of {} columns", grid.height(), grid.width()),
+            Err(e) => println!("{}", e),
+        },
+        Some("part-1") => {
+            let grid = schematic(&args);
+            println!("part 1: {}", sum_part_numbers(&grid, &symbols, adjacency));
+        }
+        Some("unclassified") => {
+            let grid = schematic(&args);
+
+            let unclassified = unclassified_characters(&grid, &symbols);
+
+            if unclassified.is_empty() {
+                println!("every character was classified");
+            }
+
+            for (c, positions) in unclassified {
+                println!(
+                    "'{}' seen {} times, first at {}",
+                    c,
+                    positions.len(),
+                    positions[0]
+                );
+            }
+        }
+        Some("gears") => {
+            let grid = schematic(&args);
+            let rule = GearRule {
+                symbols: aoc::option(&args, "gear-symbols")
+                    .map_or(GearRule::default().symbols, SymbolClass::parse),
+                attached: aoc::option(&args, "attached")
+                    .map_or(GearRule::default().attached, |a| {
+                        Attached::parse(a).unwrap()
+                    }),
+                combine: aoc::option(&args, "combine")
+                    .map_or(GearRule::default().combine, |c| Combine::parse(c).unwrap()),
+            };
+
+            let gears = find_gears(&grid, &rule, adjacency);
+
+            for gear in gears.iter() {
+                println!(
+                    "'{}' at {}: {} -> {}",
+                    gear.symbol,
+                    gear.position,
+                    gear.numbers.iter().map(|n| n.value).join(", "),
+                    rule.combine.apply(&gear.numbers)
+                );
+            }
+
+            println!(
+                "total: {}",
+                gears
+                    .iter()
+                    .map(|g| rule.combine.apply(&g.numbers))
+                    .sum::<usize>()
+            );
+        }
+        Some("render") => {
+            let grid = schematic(&args);
+            let highlights = render::highlight(&grid, &symbols, &GearRule::default(), adjacency);
+
+            if let Some(path) = aoc::option(&args, "html") {
+                std::fs::write(path, render::render_html(&grid, &highlights)).unwrap();
+            }
+
+            println!("{}", render::render_ansi(&grid, &highlights));
+        }
+        Some("graph") => {
+            let grid = schematic(&args);
+            let graph = graph::PartGraph::build(&grid, &symbols, adjacency);
+
+            if let Some(path) = aoc::option(&args, "dot") {
+                std::fs::write(path, graph.to_dot()).unwrap();
+            }
+
+            if let Some(path) = aoc::option(&args, "json") {
+                std::fs::write(path, graph.to_json()).unwrap();
+            }
+
+            let components = graph.components();
+
+            println!(
+                "{} numbers, {} symbols, {} edges, {} components",
+                graph.numbers.len(),
+                graph.symbols.len(),
+                graph.edges.len(),
+                components.len()
+            );
+
+            for (side, stats) in [
+                ("number", graph.number_degrees()),
+                ("symbol", graph.symbol_degrees()),
+            ] {
+                println!(
+                    "{} degree: min {}, max {}, mean {:.3}, histogram {:?}",
+                    side, stats.min, stats.max, stats.mean, stats.histogram
+                );
+            }
+
+            for (size, count) in components
+                .iter()
+                .map(|c| c.numbers.len() + c.symbols.len())
+                .counts()
+                .into_iter()
+                .sorted()
+            {
+                println!("{} components of size {}", count, size);
+            }
+        }
+        Some("scaling") => aoc::scaling(
+            "day03",
+            &[Dimension {
+                name: "rows",
+                generate: generate_schematic,
+                start: 100,
+            }],
+            [part_1, part_2],
+        ),
+        _ => ("day03.txt", [part_1, part_2]).solve(),
+    }
+}
+
+fn input() -> String {
+    aoc::read_input("day03.txt")
+}
+
+fn schematic(args: &[String]) -> Grid<char> {
+    let shape = match aoc::flag(args, "pad") {
+        true => Shape::Pad,
+        false => Shape::Strict,
+    };
+
+    parse_schematic(&input(), shape).unwrap_or_else(|e| panic!("{}", e))
 }
 
 fn part_1(input: &str) -> String {
-    const SYMBOLS: [char; 10] = ['+', '%', '*', '$', '#', '/', '=', '@', '-', '&'];
+    let grid = parse_schematic(input, Shape::Strict).unwrap();
+
+    sum_part_numbers(&grid, &SymbolClass::Set(SYMBOLS.into()), Adjacency::Eight).to_string()
+}
+
+fn part_2(input: &str) -> String {
+    let grid = parse_schematic(input, Shape::Strict).unwrap();
+
+    sum_gear_ratios(&grid, &GearRule::default(), Adjacency::Eight).to_string()
+}
 
-    let line_length = input.find('\n').unwrap() + 1;
-    let input: Vec<char> = input.chars().collect();
+fn sum_part_numbers(grid: &Grid<char>, symbols: &SymbolClass, adjacency: Adjacency) -> usize {
+    let numbers = extract_numbers(grid);
+    let _span = aoc::span("symbols");
 
-    extract_numbers(&input)
+    numbers
         .iter()
         .filter_map(|n| {
-            extract_adjacent_symbol_indexes(&input, *n, line_length, &SYMBOLS)
-                .is_empty()
-                .not()
-                .then_some(n.value.0)
+            let symbols = extract_adjacent_symbol_indexes(grid, *n, symbols, adjacency);
+            aoc::explain("line", n.position.row + 1, || match symbols.is_empty() {
+                true => format!("{} is not next to a symbol", n.value),
+                false => format!("{} is a part number", n.value),
+            });
+
+            symbols.is_empty().not().then_some(n.value)
         })
-        .sum::<usize>()
-        .to_string()
+        .sum()
 }
 
-fn part_2(input: &str) -> String {
-    let line_length = input.find('\n').unwrap() + 1;
-    let input: Vec<char> = input.chars().collect();
-    let numbers = extract_numbers(&input);
+fn find_gears(grid: &Grid<char>, rule: &GearRule, adjacency: Adjacency) -> Vec<Gear> {
+    let attachments = {
+        let _span = aoc::span("symbols");
+
+        extract_numbers(grid)
+            .into_iter()
+            .flat_map(|n| {
+                extract_adjacent_symbol_indexes(grid, n, &rule.symbols, adjacency)
+                    .into_iter()
+                    .map(move |p| (p, n))
+            })
+            .collect_vec()
+    };
+    let _span = aoc::span("into_group_map");
+
+    attachments
+        .into_iter()
+        .into_group_map()
+        .into_iter()
+        .filter(|(_, ns)| rule.attached.accepts(ns.len()))
+        .map(|(position, numbers)| Gear {
+            position,
+            symbol: *grid.get(position).unwrap(),
+            numbers,
+        })
+        .sorted_by_key(|g| g.position)
+        .collect()
+}
 
-    let gear_number_map = numbers
+fn sum_gear_ratios(grid: &Grid<char>, rule: &GearRule, adjacency: Adjacency) -> usize {
+    find_gears(grid, rule, adjacency)
         .iter()
-        .map(|n| {
-            extract_adjacent_symbol_indexes(&input, *n, line_length, &['*'])
-                .into_iter()
-                .map(|i| (i, *n))
+        .map(|g| {
+            let ratio = rule.combine.apply(&g.numbers);
+            aoc::explain("line", g.position.row + 1, || {
+                format!(
+                    "{} at column {} joins {} into {}",
+                    g.symbol,
+                    g.position.col + 1,
+                    g.numbers.iter().map(|n| n.value).join(", "),
+                    ratio
+                )
+            });
+
+            ratio
         })
-        .flatten()
-        .into_group_map();
+        .sum()
+}
 
-    gear_number_map
-        .values()
-        .filter(|ns| ns.len() == 2)
-        .map(|ns| ns[0].value.0 * ns[1].value.0)
-        .sum::<usize>()
-        .to_string()
+#[derive(Clone, PartialEq, Debug)]
+struct Gear {
+    position: Position,
+    symbol: char,
+    numbers: Vec<Number>,
+}
+
+struct GearRule {
+    symbols: SymbolClass,
+    attached: Attached,
+    combine: Combine,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        Self {
+            symbols: SymbolClass::Set(vec!['*']),
+            attached: Attached::Exactly(2),
+            combine: Combine::Product,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Attached {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Attached {
+    /// Parses `N` as exactly `N` numbers and `N+` as at least `N` numbers.
+    fn parse(spec: &str) -> Option<Self> {
+        match spec.strip_suffix('+') {
+            Some(n) => n.parse().ok().map(Attached::AtLeast),
+            None => spec.parse().ok().map(Attached::Exactly),
+        }
+    }
+
+    fn accepts(&self, count: usize) -> bool {
+        match self {
+            Attached::Exactly(n) => count == *n,
+            Attached::AtLeast(n) => count >= *n,
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
+enum Combine {
+    Product,
+    Sum,
+    Fold(usize, fn(usize, usize) -> usize),
+}
+
+impl Combine {
+    fn parse(spec: &str) -> Option<Self> {
+        match spec {
+            "product" => Some(Combine::Product),
+            "sum" => Some(Combine::Sum),
+            "max" => Some(Combine::Fold(0, usize::max)),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, numbers: &[Number]) -> usize {
+        let values = numbers.iter().map(|n| n.value);
+
+        match self {
+            Combine::Product => values.product(),
+            Combine::Sum => values.sum(),
+            Combine::Fold(init, f) => values.fold(*init, f),
+        }
+    }
+}
+
+/// Groups the positions of every character that is neither a digit, `.` nor
+/// a symbol under `symbols`.
+fn unclassified_characters(
+    grid: &Grid<char>,
+    symbols: &SymbolClass,
+) -> BTreeMap<char, Vec<Position>> {
+    grid.iter()
+        .filter(|(_, c)| !c.is_ascii_digit() && **c != '.' && !symbols.matches(**c))
+        .fold(BTreeMap::new(), |mut seen, (p, c)| {
+            seen.entry(*c).or_insert_with(Vec::new).push(p);
+            seen
+        })
+}
+
+enum SymbolClass {
+    Set(Vec<char>),
+    NotDigitOrDot,
+    Predicate(fn(char) -> bool),
+}
+
+impl SymbolClass {
+    /// Parses `any` as [`SymbolClass::NotDigitOrDot`], `punctuation` as a
+    /// predicate on ASCII punctuation other than `.`, and anything else as the
+    /// set of its characters.
+    fn parse(spec: &str) -> Self {
+        match spec {
+            "any" => SymbolClass::NotDigitOrDot,
+            "punctuation" => SymbolClass::Predicate(|c| c.is_ascii_punctuation() && c != '.'),
+            set => SymbolClass::Set(set.chars().collect()),
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            SymbolClass::Set(symbols) => symbols.contains(&c),
+            SymbolClass::NotDigitOrDot => !c.is_ascii_digit() && c != '.',
+            SymbolClass::Predicate(predicate) => predicate(c),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Adjacency {
+    Eight,
+    Four,
+    Manhattan(usize),
+}
+
+impl Adjacency {
+    fn parse(spec: &str) -> Option<Self> {
+        match spec.split_once(':') {
+            None if spec == "8" => Some(Adjacency::Eight),
+            None if spec == "4" => Some(Adjacency::Four),
+            Some(("manhattan", radius)) => radius.parse().ok().map(Adjacency::Manhattan),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Shape {
+    Strict,
+    Pad,
+}
+
+#[derive(PartialEq, Debug)]
+enum SchematicError {
+    /// Lines whose length differs from the first one, as pairs of 1-based line
+    /// number and length.
+    RaggedLines {
+        expected: usize,
+        lines: Vec<(usize, usize)>,
+    },
+    /// A number too large for a `usize`, starting at `Position`.
+    NumberTooLarge(Position),
+}
+
+impl std::fmt::Display for SchematicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchematicError::RaggedLines { expected, lines } => write!(
+                f,
+                "expected every line to be {} characters long, but {}",
+                expected,
+                lines
+                    .iter()
+                    .map(|(line, length)| format!("line {} has {}", line, length))
+                    .join(", ")
+            ),
+            SchematicError::NumberTooLarge(position) => {
+                write!(f, "number at {} is larger than {}", position, usize::MAX)
+            }
+        }
+    }
+}
+
+/// Checks that every line of the schematic is as long as the first one, or
+/// pads shorter lines with `.` up to the longest one, and that every number
+/// fits in a `usize`.
+fn parse_schematic(input: &str, shape: Shape) -> Result<Grid<char>, SchematicError> {
+    let _span = aoc::span("parse");
+    let lengths = input.lines().map(|l| l.chars().count()).collect_vec();
+    let expected = lengths.first().copied().unwrap_or(0);
+
+    let width = match shape {
+        Shape::Strict => {
+            let lines = lengths
+                .iter()
+                .enumerate()
+                .filter(|(_, length)| **length != expected)
+                .map(|(i, length)| (i + 1, *length))
+                .collect_vec();
+
+            if !lines.is_empty() {
+                return Err(SchematicError::RaggedLines { expected, lines });
+            }
+
+            expected
+        }
+        Shape::Pad => lengths.iter().copied().max().unwrap_or(0),
+    };
+
+    let cells = input
+        .lines()
+        .flat_map(|l| l.chars().chain(std::iter::repeat('.')).take(width))
+        .collect();
+
+    let grid = Grid::from_cells(width, cells).unwrap();
+
+    match find_number_too_large(&grid) {
+        Some(position) => Err(SchematicError::NumberTooLarge(position)),
+        None => Ok(grid),
+    }
+}
+
+fn find_number_too_large(grid: &Grid<char>) -> Option<Position> {
+    (0..grid.height()).find_map(|row| {
+        grid.row(row)
+            .group_by(|(_, c)| c.is_ascii_digit())
+            .into_iter()
+            .filter(|(is_digit, _)| *is_digit)
+            .find_map(|(_, digits)| {
+                let digits = digits.collect_vec();
+                let text = digits.iter().map(|(_, c)| **c).collect::<String>();
+
+                text.parse::<usize>().is_err().then_some(digits[0].0)
+            })
+    })
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
 struct Number {
-    index: Wrapping<usize>,
-    length: Wrapping<usize>,
-    value: Wrapping<usize>,
+    position: Position,
+    length: usize,
+    value: usize,
 }
 
-fn extract_numbers(input: &[char]) -> Vec<Number> {
-    input
-        .iter()
-        .enumerate()
-        .fold(
-            (Vec::new(), None),
-            |(mut numbers, mut previous_number), (i, c)| {
-                previous_number = match (c.is_digit(10), previous_number) {
-                    (true, None) => Number {
-                        index: Wrapping(i),
-                        length: Wrapping(1),
-                        value: Wrapping(c.to_digit(10).unwrap() as usize),
-                    }
-                    .into(),
-                    (true, Some(n)) if i + 1 == input.len() => {
-                        numbers.push(Number {
-                            length: n.length + Wrapping(1),
-                            value: n.value * Wrapping(10)
-                                + Wrapping(c.to_digit(10).unwrap() as usize),
-                            ..n
-                        });
-                        None
-                    }
-                    (true, Some(n)) => Number {
-                        length: n.length + Wrapping(1),
-                        value: n.value * Wrapping(10) + Wrapping(c.to_digit(10).unwrap() as usize),
-                        ..n
-                    }
-                    .into(),
-                    (false, Some(n)) => {
-                        numbers.push(n);
-                        None
-                    }
-                    (false, None) => None,
-                };
-
-                (numbers, previous_number)
-            },
-        )
-        .0
+/// A schematic of `rows` rows as wide as the real one, mostly dots with
+/// numbers of up to three digits and symbols scattered in between.
+fn generate_schematic(rows: usize, rng: &mut Rng) -> String {
+    const WIDTH: usize = 140;
+
+    (0..rows)
+        .map(|_| {
+            let mut row = String::new();
+
+            while row.len() < WIDTH {
+                match rng.below(10) {
+                    0..=5 => row.push('.'),
+                    6..=8 => row.extend(
+                        (0..1 + rng.below(3))
+                            .map(|_| char::from_digit(rng.below(10) as u32, 10).unwrap()),
+                    ),
+                    _ => row.push(SYMBOLS[rng.below(SYMBOLS.len() as u64) as usize]),
+                }
+            }
+
+            row.truncate(WIDTH);
+            row + "\n"
+        })
+        .collect()
+}
+
+fn extract_numbers(grid: &Grid<char>) -> Vec<Number> {
+    let _span = aoc::span("extract_numbers");
+
+    (0..grid.height())
+        .flat_map(|row| {
+            grid.row(row)
+                .group_by(|(_, c)| c.is_ascii_digit())
+                .into_iter()
+                .filter(|(is_digit, _)| *is_digit)
+                .filter_map(|(_, digits)| {
+                    digits.fold(None, |number: Option<Number>, (p, c)| {
+                        let digit = c.to_digit(10).unwrap() as usize;
+
+                        Some(match number {
+                            None => Number {
+                                position: p,
+                                length: 1,
+                                value: digit,
+                            },
+                            Some(n) => Number {
+                                length: n.length + 1,
+                                value: n
+                                    .value
+                                    .checked_mul(10)
+                                    .and_then(|v| v.checked_add(digit))
+                                    .expect("parse_schematic rejects numbers past usize::MAX"),
+                                ..n
+                            },
+                        })
+                    })
+                })
+                .collect_vec()
+        })
+        .collect()
 }
 
 fn extract_adjacent_symbol_indexes(
-    input: &Vec<char>,
+    grid: &Grid<char>,
     number: Number,
-    line_length: usize,
-    symbols: &[char],
-) -> Vec<usize> {
-    let line_length = Wrapping(line_length);
-
-    let above = (number.index - line_length - Wrapping(1)).0
-        ..(number.index + number.length - line_length + Wrapping(1)).0;
-    let around = (number.index - Wrapping(1)).0..(number.index + number.length + Wrapping(1)).0;
-    let below = (number.index + line_length - Wrapping(1)).0
-        ..(number.index + number.length + line_length + Wrapping(1)).0;
-
-    above
-        .chain(around)
-        .chain(below)
-        .filter_map(|index| {
-            input
-                .get(index)
-                .is_some_and(|c| symbols.contains(c))
-                .then(|| index)
-        })
-        .collect::<Vec<usize>>()
+    symbols: &SymbolClass,
+    adjacency: Adjacency,
+) -> Vec<Position> {
+    let Position { row, col } = number.position;
+    let last_col = col + number.length - 1;
+    let digits = (col..=last_col).map(|c| Position::new(row, c));
+
+    // A cell touches the number when it is a neighbor of any of its digits.
+    let cells = match adjacency {
+        Adjacency::Eight => digits.flat_map(|p| grid.neighbors_8(p)).collect_vec(),
+        Adjacency::Four => digits.flat_map(|p| grid.neighbors_4(p)).collect_vec(),
+        Adjacency::Manhattan(radius) => grid
+            .region(
+                row.saturating_sub(radius)..row + radius + 1,
+                col.saturating_sub(radius)..last_col + radius + 1,
+            )
+            .filter(|(p, _)| {
+                let col_distance = col.saturating_sub(p.col) + p.col.saturating_sub(last_col);
+
+                p.row.abs_diff(row) + col_distance <= radius
+            })
+            .collect_vec(),
+    };
+
+    cells
+        .into_iter()
+        .filter(|(p, _)| p.row != row || !(col..=last_col).contains(&p.col))
+        .filter(|(_, c)| symbols.matches(**c))
+        .map(|(p, _)| p)
+        .sorted()
+        .dedup()
+        .collect()
 }
 
 #[cfg(test)]
@@ -128,7 +572,7 @@ mod tests {
     use super::*;
     use rstest::rstest;
 
-    const EXAMPLE_SCHEMATIC: &str = "467..114..
+    pub const EXAMPLE_SCHEMATIC: &str = "467..114..
 ...*......
 ..35..633.
 ......#...
@@ -198,28 +642,29 @@ mod tests {
         #[case] expected_indexes: Vec<usize>,
         #[case] expected_lenghts: Vec<usize>,
     ) {
-        let input: Vec<char> = input.chars().collect();
+        let grid = parse_schematic(input, Shape::Strict).unwrap();
+        let line_length = grid.width() + 1;
 
         assert_eq!(
-            extract_numbers(&input)
+            extract_numbers(&grid)
                 .iter()
-                .map(|n| n.value.0)
+                .map(|n| n.value)
                 .collect::<Vec<usize>>(),
             expected_values
         );
 
         assert_eq!(
-            extract_numbers(&input)
+            extract_numbers(&grid)
                 .iter()
-                .map(|n| n.index.0)
+                .map(|n| n.position.row * line_length + n.position.col)
                 .collect::<Vec<usize>>(),
             expected_indexes
         );
 
         assert_eq!(
-            extract_numbers(&input)
+            extract_numbers(&grid)
                 .iter()
-                .map(|n| n.length.0)
+                .map(|n| n.length)
                 .collect::<Vec<usize>>(),
             expected_lenghts
         );
@@ -243,4 +688,150 @@ mod tests {
     fn part_2_test(#[case] input: &str, #[case] expected: &str) {
         assert_eq!(part_2(input), expected);
     }
+
+    #[rstest]
+    #[case(TEST_SCHEMATIC_3, Adjacency::Eight, 6)]
+    #[case(TEST_SCHEMATIC_3, Adjacency::Four, 0)]
+    #[case(TEST_SCHEMATIC_3, Adjacency::Manhattan(2), 6)]
+    #[case(TEST_SCHEMATIC_2, Adjacency::Four, 505)]
+    #[case(TEST_SCHEMATIC_2, Adjacency::Manhattan(3), 1133)]
+    fn adjacency_test(#[case] input: &str, #[case] adjacency: Adjacency, #[case] expected: usize) {
+        let grid = parse_schematic(input, Shape::Strict).unwrap();
+
+        assert_eq!(
+            sum_part_numbers(&grid, &SymbolClass::Set(SYMBOLS.into()), adjacency),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(SymbolClass::Set(SYMBOLS.into()), 0, ['!', '?'].into())]
+    #[case(SymbolClass::Set(vec!['!']), 12, ['?'].into())]
+    #[case(SymbolClass::NotDigitOrDot, 46, [].into())]
+    #[case(SymbolClass::Predicate(|c| c == '?'), 34, ['!'].into())]
+    #[case(SymbolClass::parse("punctuation"), 46, [].into())]
+    fn symbol_class_test(
+        #[case] symbols: SymbolClass,
+        #[case] expected_sum: usize,
+        #[case] expected_unclassified: Vec<char>,
+    ) {
+        let grid = parse_schematic("12!.\n....\n.?34", Shape::Strict).unwrap();
+
+        assert_eq!(
+            sum_part_numbers(&grid, &symbols, Adjacency::Eight),
+            expected_sum
+        );
+        assert_eq!(
+            unclassified_characters(&grid, &symbols)
+                .into_keys()
+                .collect::<Vec<char>>(),
+            expected_unclassified
+        );
+    }
+
+    #[rstest]
+    #[case(GearRule::default(), 467835)]
+    #[case(GearRule { attached: Attached::AtLeast(1), ..Default::default() }, 467835 + 617)]
+    #[case(GearRule { combine: Combine::Sum, ..Default::default() }, 467 + 35 + 755 + 598)]
+    #[case(GearRule { combine: Combine::Fold(0, usize::max), ..Default::default() }, 467 + 755)]
+    #[case(GearRule { symbols: SymbolClass::Set(vec!['*', '#', '+']), attached: Attached::AtLeast(1), ..Default::default() }, 467835 + 617 + 633 + 592)]
+    #[case(GearRule { attached: Attached::Exactly(3), ..Default::default() }, 0)]
+    fn gear_rule_test(#[case] rule: GearRule, #[case] expected: usize) {
+        let grid = parse_schematic(EXAMPLE_SCHEMATIC, Shape::Strict).unwrap();
+
+        assert_eq!(sum_gear_ratios(&grid, &rule, Adjacency::Eight), expected);
+    }
+
+    #[test]
+    fn find_gears_test() {
+        let grid = parse_schematic(EXAMPLE_SCHEMATIC, Shape::Strict).unwrap();
+
+        assert_eq!(
+            find_gears(&grid, &GearRule::default(), Adjacency::Eight)
+                .into_iter()
+                .map(|g| (
+                    g.position,
+                    g.symbol,
+                    g.numbers.iter().map(|n| n.value).collect::<Vec<usize>>()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (Position::new(1, 3), '*', vec![467, 35]),
+                (Position::new(8, 5), '*', vec![755, 598]),
+            ]
+        );
+    }
+
+    const SINGLE_LINE_SCHEMATIC: &str = "467*11...5";
+
+    const SINGLE_COLUMN_SCHEMATIC: &str = "1
+*
+2
+.
+3
+#";
+
+    const RAGGED_SCHEMATIC: &str = "467..114..
+...*.....
+..35
+......#...
+.....633";
+
+    #[rstest]
+    #[case(SINGLE_LINE_SCHEMATIC, Shape::Strict, Ok(478))]
+    #[case(SINGLE_COLUMN_SCHEMATIC, Shape::Strict, Ok(6))]
+    #[case("", Shape::Strict, Ok(0))]
+    #[case(RAGGED_SCHEMATIC, Shape::Strict, Err(SchematicError::RaggedLines { expected: 10, lines: vec![(2, 9), (3, 4), (5, 8)] }))]
+    #[case(RAGGED_SCHEMATIC, Shape::Pad, Ok(467 + 35 + 633))]
+    #[case("1\n\n#", Shape::Strict, Err(SchematicError::RaggedLines { expected: 1, lines: vec![(2, 0)] }))]
+    #[case("1\n\n#", Shape::Pad, Ok(0))]
+    #[case(".\n1*", Shape::Pad, Ok(1))]
+    fn parse_schematic_test(
+        #[case] input: &str,
+        #[case] shape: Shape,
+        #[case] expected: Result<usize, SchematicError>,
+    ) {
+        assert_eq!(
+            parse_schematic(input, shape).map(|grid| sum_part_numbers(
+                &grid,
+                &SymbolClass::Set(SYMBOLS.into()),
+                Adjacency::Eight
+            )),
+            expected
+        );
+    }
+
+    #[test]
+    fn number_too_large_test() {
+        let input = format!("..{}*", "9".repeat(20));
+
+        assert_eq!(
+            parse_schematic(&input, Shape::Strict),
+            Err(SchematicError::NumberTooLarge(Position::new(0, 2)))
+        );
+        assert_eq!(
+            parse_schematic(&format!("{}*", usize::MAX), Shape::Strict)
+                .map(|grid| extract_numbers(&grid)[0].value),
+            Ok(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn schematic_error_test() {
+        assert_eq!(
+            parse_schematic(RAGGED_SCHEMATIC, Shape::Strict)
+                .unwrap_err()
+                .to_string(),
+            "expected every line to be 10 characters long, but line 2 has 9, line 3 has 4, line 5 has 8"
+        );
+    }
+
+    #[test]
+    fn generate_schematic_test() {
+        let grid =
+            parse_schematic(&generate_schematic(20, &mut Rng::new(3)), Shape::Strict).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (140, 20));
+        assert!(!extract_numbers(&grid).is_empty());
+    }
 }
@@ -0,0 +1,294 @@
+use super::{extract_adjacent_symbol_indexes, extract_numbers, Adjacency, Number, SymbolClass};
+use crate::aoc::escape_json;
+use crate::grid::{Grid, Position};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+
+/// Bipartite graph linking part numbers to the symbols they touch.
+pub struct PartGraph {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<(Position, char)>,
+    /// Pairs of indexes into `numbers` and `symbols`.
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Component {
+    pub numbers: Vec<usize>,
+    pub symbols: Vec<usize>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct DegreeStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub histogram: BTreeMap<usize, usize>,
+}
+
+impl PartGraph {
+    pub fn build(grid: &Grid<char>, symbols: &SymbolClass, adjacency: Adjacency) -> Self {
+        let numbers = extract_numbers(grid);
+        let symbol_cells = grid
+            .iter()
+            .filter(|(_, c)| symbols.matches(**c) && !c.is_ascii_digit())
+            .map(|(p, c)| (p, *c))
+            .collect_vec();
+        let symbol_ids: BTreeMap<Position, usize> = symbol_cells
+            .iter()
+            .enumerate()
+            .map(|(i, (p, _))| (*p, i))
+            .collect();
+
+        let edges = numbers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, n)| {
+                extract_adjacent_symbol_indexes(grid, *n, symbols, adjacency)
+                    .into_iter()
+                    .filter_map(|p| symbol_ids.get(&p))
+                    .map(move |s| (i, *s))
+            })
+            .collect();
+
+        Self {
+            numbers,
+            symbols: symbol_cells,
+            edges,
+        }
+    }
+
+    /// Splits the graph into connected components, isolated nodes included,
+    /// ordered by their first number or symbol.
+    pub fn components(&self) -> Vec<Component> {
+        let offset = self.numbers.len();
+        let mut parents = (0..offset + self.symbols.len()).collect_vec();
+
+        fn root(parents: &mut [usize], node: usize) -> usize {
+            let mut node = node;
+
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+
+            node
+        }
+
+        for (n, s) in self.edges.iter() {
+            let (a, b) = (root(&mut parents, *n), root(&mut parents, offset + s));
+            parents[a.max(b)] = a.min(b);
+        }
+
+        (0..parents.len())
+            .map(|node| (root(&mut parents, node), node))
+            .into_group_map()
+            .into_iter()
+            .sorted()
+            .map(|(_, nodes)| Component {
+                numbers: nodes.iter().filter(|n| **n < offset).copied().collect(),
+                symbols: nodes
+                    .iter()
+                    .filter(|n| **n >= offset)
+                    .map(|n| n - offset)
+                    .collect(),
+            })
+            .collect()
+    }
+
+    pub fn number_degrees(&self) -> DegreeStats {
+        DegreeStats::from_degrees(self.degrees(self.numbers.len(), |(n, _)| *n))
+    }
+
+    pub fn symbol_degrees(&self) -> DegreeStats {
+        DegreeStats::from_degrees(self.degrees(self.symbols.len(), |(_, s)| *s))
+    }
+
+    fn degrees(&self, nodes: usize, side: fn(&(usize, usize)) -> usize) -> Vec<usize> {
+        self.edges.iter().fold(vec![0; nodes], |mut degrees, e| {
+            degrees[side(e)] += 1;
+            degrees
+        })
+    }
+
+    pub fn to_dot(&self) -> String {
+        let numbers = self
+            .numbers
+            .iter()
+            .enumerate()
+            .map(|(i, n)| format!("  n{} [label=\"{}\"];\n", i, n.value));
+        let symbols = self.symbols.iter().enumerate().map(|(i, (_, c))| {
+            format!(
+                "  s{} [label=\"{}\", shape=box];\n",
+                i,
+                escape_dot(&c.to_string())
+            )
+        });
+        let edges = self
+            .edges
+            .iter()
+            .map(|(n, s)| format!("  n{} -- s{};\n", n, s));
+
+        format!(
+            "graph parts {{\n{}}}\n",
+            numbers.chain(symbols).chain(edges).collect::<String>()
+        )
+    }
+
+    pub fn to_json(&self) -> String {
+        let numbers = self
+            .numbers
+            .iter()
+            .map(|n| {
+                format!(
+                    "{{\"value\":{},\"row\":{},\"col\":{},\"length\":{}}}",
+                    n.value, n.position.row, n.position.col, n.length
+                )
+            })
+            .join(",");
+        let symbols = self
+            .symbols
+            .iter()
+            .map(|(p, c)| {
+                format!(
+                    "{{\"symbol\":\"{}\",\"row\":{},\"col\":{}}}",
+                    escape_json(&c.to_string()),
+                    p.row,
+                    p.col
+                )
+            })
+            .join(",");
+        let edges = self
+            .edges
+            .iter()
+            .map(|(n, s)| format!("[{},{}]", n, s))
+            .join(",");
+
+        format!(
+            "{{\"numbers\":[{}],\"symbols\":[{}],\"edges\":[{}]}}",
+            numbers, symbols, edges
+        )
+    }
+}
+
+impl DegreeStats {
+    fn from_degrees(degrees: Vec<usize>) -> Self {
+        Self {
+            min: degrees.iter().copied().min().unwrap_or(0),
+            max: degrees.iter().copied().max().unwrap_or(0),
+            mean: match degrees.len() {
+                0 => 0.0,
+                len => degrees.iter().sum::<usize>() as f64 / len as f64,
+            },
+            histogram: degrees.into_iter().counts().into_iter().collect(),
+        }
+    }
+}
+
+/// Escapes a string for use inside a double-quoted DOT string, which only
+/// gives a meaning to quotes and backslashes.
+fn escape_dot(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::EXAMPLE_SCHEMATIC;
+    use crate::{parse_schematic, Shape};
+
+    #[test]
+    fn components_test() {
+        let grid = parse_schematic(EXAMPLE_SCHEMATIC, Shape::Strict).unwrap();
+        let graph = PartGraph::build(&grid, &SymbolClass::NotDigitOrDot, Adjacency::Eight);
+
+        assert_eq!(graph.numbers.len(), 10);
+        assert_eq!(graph.symbols.len(), 6);
+        assert_eq!(graph.edges.len(), 8);
+        assert_eq!(
+            graph
+                .components()
+                .iter()
+                .map(|c| (
+                    c.numbers
+                        .iter()
+                        .map(|n| graph.numbers[*n].value)
+                        .collect_vec(),
+                    c.symbols.iter().map(|s| graph.symbols[*s].1).collect_vec()
+                ))
+                .collect_vec(),
+            vec![
+                (vec![467, 35], vec!['*']),
+                (vec![114], vec![]),
+                (vec![633], vec!['#']),
+                (vec![617], vec!['*']),
+                (vec![58], vec![]),
+                (vec![592], vec!['+']),
+                (vec![755, 598], vec!['*']),
+                (vec![664], vec!['$']),
+            ]
+        );
+    }
+
+    #[test]
+    fn degrees_test() {
+        let grid = parse_schematic(EXAMPLE_SCHEMATIC, Shape::Strict).unwrap();
+        let graph = PartGraph::build(&grid, &SymbolClass::NotDigitOrDot, Adjacency::Eight);
+
+        assert_eq!(
+            graph.number_degrees(),
+            DegreeStats {
+                min: 0,
+                max: 1,
+                mean: 0.8,
+                histogram: [(0, 2), (1, 8)].into(),
+            }
+        );
+        assert_eq!(
+            graph.symbol_degrees(),
+            DegreeStats {
+                min: 1,
+                max: 2,
+                mean: 8.0 / 6.0,
+                histogram: [(1, 4), (2, 2)].into(),
+            }
+        );
+    }
+
+    #[test]
+    fn export_test() {
+        let grid = parse_schematic("1\"\n.2", Shape::Strict).unwrap();
+        let graph = PartGraph::build(&grid, &SymbolClass::NotDigitOrDot, Adjacency::Eight);
+
+        assert_eq!(
+            graph.to_dot(),
+            "graph parts {
+  n0 [label=\"1\"];
+  n1 [label=\"2\"];
+  s0 [label=\"\\\"\", shape=box];
+  n0 -- s0;
+  n1 -- s0;
+}
+"
+        );
+        assert_eq!(
+            graph.to_json(),
+            "{\"numbers\":[{\"value\":1,\"row\":0,\"col\":0,\"length\":1},\
+             {\"value\":2,\"row\":1,\"col\":1,\"length\":1}],\
+             \"symbols\":[{\"symbol\":\"\\\"\",\"row\":0,\"col\":1}],\
+             \"edges\":[[0,0],[1,0]]}"
+        );
+
+        let grid = parse_schematic("1\u{7}", Shape::Strict).unwrap();
+        let graph = PartGraph::build(&grid, &SymbolClass::NotDigitOrDot, Adjacency::Eight);
+
+        assert!(graph.to_dot().contains("s0 [label=\"\u{7}\", shape=box]"));
+        assert!(graph.to_json().contains("\"symbol\":\"\\u0007\""));
+    }
+}
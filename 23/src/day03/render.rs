@@ -0,0 +1,169 @@
+use super::{
+    extract_adjacent_symbol_indexes, extract_numbers, find_gears, Adjacency, GearRule, SymbolClass,
+};
+use crate::aoc::escape_xml;
+use crate::grid::{Grid, Position};
+use itertools::Itertools;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Highlight {
+    Plain,
+    PartNumber,
+    IsolatedNumber,
+    Gear,
+    GearNumber,
+}
+
+impl Highlight {
+    fn ansi(&self) -> &'static str {
+        match self {
+            Highlight::Plain => "",
+            Highlight::PartNumber => "\x1b[32m",
+            Highlight::IsolatedNumber => "\x1b[31m",
+            Highlight::Gear => "\x1b[30;43m",
+            Highlight::GearNumber => "\x1b[1;33m",
+        }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Highlight::Plain => "plain",
+            Highlight::PartNumber => "part",
+            Highlight::IsolatedNumber => "isolated",
+            Highlight::Gear => "gear",
+            Highlight::GearNumber => "gear-number",
+        }
+    }
+}
+
+/// Marks every cell of the schematic with how it takes part in the answer.
+/// Gears and the numbers attached to them take precedence over plain part
+/// numbers.
+pub fn highlight(
+    grid: &Grid<char>,
+    symbols: &SymbolClass,
+    gears: &GearRule,
+    adjacency: Adjacency,
+) -> Grid<Highlight> {
+    let mut highlights = grid.map(|_| Highlight::Plain);
+
+    let mut paint = |position: Position, length: usize, highlight: Highlight| {
+        for col in position.col..position.col + length {
+            *highlights
+                .get_mut(Position::new(position.row, col))
+                .unwrap() = highlight;
+        }
+    };
+
+    for number in extract_numbers(grid) {
+        let highlight =
+            match extract_adjacent_symbol_indexes(grid, number, symbols, adjacency).is_empty() {
+                true => Highlight::IsolatedNumber,
+                false => Highlight::PartNumber,
+            };
+
+        paint(number.position, number.length, highlight);
+    }
+
+    for gear in find_gears(grid, gears, adjacency) {
+        paint(gear.position, 1, Highlight::Gear);
+
+        for number in gear.numbers {
+            paint(number.position, number.length, Highlight::GearNumber);
+        }
+    }
+
+    highlights
+}
+
+pub fn render_ansi(grid: &Grid<char>, highlights: &Grid<Highlight>) -> String {
+    render(grid, highlights, |highlight, text| match highlight {
+        Highlight::Plain => text.to_string(),
+        _ => format!("{}{}\x1b[0m", highlight.ansi(), text),
+    })
+}
+
+pub fn render_html(grid: &Grid<char>, highlights: &Grid<Highlight>) -> String {
+    let body = render(grid, highlights, |highlight, text| {
+        let text = escape_xml(text);
+
+        match highlight {
+            Highlight::Plain => text,
+            _ => format!("<span class=\"{}\">{}</span>", highlight.class(), text),
+        }
+    });
+
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>day03 schematic</title>
+<style>
+body {{ background: #1e1e1e; color: #808080; }}
+.part {{ color: #4ec94e; }}
+.isolated {{ color: #e05252; }}
+.gear {{ color: #1e1e1e; background: #e0c040; }}
+.gear-number {{ color: #e0c040; font-weight: bold; }}
+</style>
+</head>
+<body>
+<pre>
+{}
+</pre>
+</body>
+</html>
+",
+        body
+    )
+}
+
+/// Renders the schematic row by row, passing every run of equally highlighted
+/// cells to `paint`.
+fn render(
+    grid: &Grid<char>,
+    highlights: &Grid<Highlight>,
+    paint: impl Fn(Highlight, &str) -> String,
+) -> String {
+    (0..grid.height())
+        .map(|row| {
+            grid.row(row)
+                .group_by(|(p, _)| *highlights.get(*p).unwrap())
+                .into_iter()
+                .map(|(highlight, cells)| {
+                    paint(highlight, &cells.map(|(_, c)| c).collect::<String>())
+                })
+                .collect::<String>()
+        })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_schematic, Shape};
+
+    const TEST_SCHEMATIC: &str = "12*3
+....
+<5..";
+
+    #[test]
+    fn highlight_test() {
+        let grid = parse_schematic(TEST_SCHEMATIC, Shape::Strict).unwrap();
+        let highlights = highlight(
+            &grid,
+            &SymbolClass::Set(vec!['*']),
+            &GearRule::default(),
+            Adjacency::Eight,
+        );
+
+        assert_eq!(
+            render_ansi(&grid, &highlights),
+            "\x1b[1;33m12\x1b[0m\x1b[30;43m*\x1b[0m\x1b[1;33m3\x1b[0m\n....\n<\x1b[31m5\x1b[0m.."
+        );
+        assert!(render_html(&grid, &highlights).contains(
+            "<span class=\"gear-number\">12</span><span class=\"gear\">*</span>\
+             <span class=\"gear-number\">3</span>\n....\n&lt;<span class=\"isolated\">5</span>.."
+        ));
+    }
+}
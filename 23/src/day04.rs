@@ -1,77 +1,408 @@
 mod aoc;
 
-use aoc::Solver;
+use aoc::{Dimension, Rng, Solver};
 use itertools::Itertools;
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 fn main() {
-    ("day04.txt", [part_1, part_2]).solve();
+    let args = aoc::args();
+
+    match args.first().map(String::as_str) {
+        Some("check") => {
+            let issues = check_cards(&aoc::read_input("day04.txt"));
+
+            if issues.is_empty() {
+                println!("no issues found");
+            }
+
+            for issue in issues {
+                println!("{}", issue);
+            }
+        }
+        Some("compare") => {
+            let cards = cards(&aoc::read_input("day04.txt"));
+            let rules = match args[1..].is_empty() {
+                true => ["doubling", "linear", "fibonacci", "cascade:2"]
+                    .map(|r| scoring_rule(r).unwrap())
+                    .into(),
+                false => args[1..]
+                    .iter()
+                    .map(|r| scoring_rule(r).unwrap_or_else(|| panic!("unknown rule {}", r)))
+                    .collect_vec(),
+            };
+
+            println!("{:<12} {:>20} {:>20}", "rule", "points", "cards");
+
+            for rule in rules {
+                println!(
+                    "{:<12} {:>20} {:>20}",
+                    rule.name(),
+                    total_points(&cards, rule.as_ref()),
+                    total_cards(&cards, rule.as_ref())
+                );
+            }
+        }
+        Some("scaling") => aoc::scaling(
+            "day04",
+            &[
+                Dimension {
+                    name: "cards",
+                    generate: |cards, rng| generate_cards(cards, 10, 25, rng),
+                    start: 1000,
+                },
+                Dimension {
+                    name: "numbers",
+                    generate: |numbers, rng| generate_cards(20, numbers, numbers, rng),
+                    start: 200,
+                },
+            ],
+            [part_1, part_2],
+        ),
+        _ => ("day04.txt", [part_1, part_2]).solve(),
+    }
 }
 
 fn part_1(input: &str) -> String {
+    total_points(&cards(input), &Doubling).to_string()
+}
+
+fn part_2(input: &str) -> String {
+    total_cards(&cards(input), &Doubling).to_string()
+}
+
+fn cards(input: &str) -> Vec<Card> {
     parse_cards(input)
+        .unwrap_or_else(|issues| panic!("invalid cards: {}", issues.iter().join("; ")))
+}
+
+fn total_points(cards: &[Card], rule: &dyn ScoringRule) -> BigUint {
+    let _span = aoc::span("points");
+
+    cards
         .iter()
-        .map(Card::points)
-        .sum::<usize>()
-        .to_string()
+        .map(|card| {
+            let points = rule.points(card.matches());
+            aoc::explain("card", card.id, || format!("scores {} points", points));
+
+            points
+        })
+        .sum()
 }
 
-fn part_2(input: &str) -> String {
-    let card_matches = parse_cards(input).iter().map(Card::matches).collect_vec();
-    let mut counts = vec![1; card_matches.len()];
+fn total_cards(cards: &[Card], rule: &dyn ScoringRule) -> BigUint {
+    let card_matches = cards.iter().map(|c| (c.id, c.matches())).collect();
+
+    count_copies(&card_matches, rule).into_values().sum()
+}
+
+/// Counts how many copies of each card id end up being scratched. Copies won
+/// past the last id are dropped.
+fn count_copies(
+    card_matches: &BTreeMap<usize, usize>,
+    rule: &dyn ScoringRule,
+) -> BTreeMap<usize, BigUint> {
+    let _span = aoc::span("count_copies");
+    let mut counts: BTreeMap<usize, BigUint> = card_matches
+        .keys()
+        .map(|id| (*id, BigUint::from(1u8)))
+        .collect();
+
+    for (id, matches) in card_matches.iter() {
+        let (span, multiplier) = rule.copies(*matches);
+        let count = &counts[id] * multiplier;
+        aoc::explain("card", *id, || match span {
+            0 => format!("has {} copies and wins nothing", counts[id]),
+            _ => format!(
+                "has {} copies and wins {} copies of cards {} to {}",
+                counts[id],
+                count,
+                id + 1,
+                id.saturating_add(span)
+            ),
+        });
+
+        for (_, won) in counts.range_mut(id + 1..id.saturating_add(span).saturating_add(1)) {
+            *won += &count;
+        }
+    }
+
+    counts
+}
+
+trait ScoringRule {
+    fn name(&self) -> String;
+
+    /// Points scored by a card with `matches` winning numbers.
+    fn points(&self, matches: usize) -> BigUint;
 
-    for (i, matches) in card_matches.iter().enumerate() {
-        for offset in 1..matches + 1 {
-            counts[i + offset] += counts[i];
+    /// How many of the following cards a card with `matches` winning numbers
+    /// wins, and how many copies of each.
+    fn copies(&self, matches: usize) -> (usize, usize) {
+        (matches, 1)
+    }
+}
+
+/// One point for the first match, doubled for every match after it.
+struct Doubling;
+
+/// One point per match.
+struct Linear;
+
+/// Points follow the Fibonacci sequence: 1, 2, 3, 5, 8, …
+struct Fibonacci;
+
+/// Doubling points, but every match wins `multiplier` copies of the next card
+/// instead of one.
+struct Cascade {
+    multiplier: usize,
+}
+
+impl ScoringRule for Doubling {
+    fn name(&self) -> String {
+        "doubling".to_string()
+    }
+
+    fn points(&self, matches: usize) -> BigUint {
+        match matches {
+            0 => BigUint::ZERO,
+            matches => BigUint::from(1u8) << (matches - 1),
+        }
+    }
+}
+
+impl ScoringRule for Linear {
+    fn name(&self) -> String {
+        "linear".to_string()
+    }
+
+    fn points(&self, matches: usize) -> BigUint {
+        BigUint::from(matches)
+    }
+}
+
+impl ScoringRule for Fibonacci {
+    fn name(&self) -> String {
+        "fibonacci".to_string()
+    }
+
+    fn points(&self, matches: usize) -> BigUint {
+        match matches {
+            0 => BigUint::ZERO,
+            matches => {
+                let (mut a, mut b) = (BigUint::ZERO, BigUint::from(1u8));
+
+                for _ in 0..matches {
+                    (a, b) = (b.clone(), a + b);
+                }
+
+                b
+            }
         }
     }
+}
 
-    counts.iter().sum::<usize>().to_string()
+impl ScoringRule for Cascade {
+    fn name(&self) -> String {
+        format!("cascade:{}", self.multiplier)
+    }
+
+    fn points(&self, matches: usize) -> BigUint {
+        Doubling.points(matches)
+    }
+
+    fn copies(&self, matches: usize) -> (usize, usize) {
+        (matches, self.multiplier)
+    }
+}
+
+/// Parses `doubling`, `linear`, `fibonacci` or `cascade:<multiplier>`.
+fn scoring_rule(spec: &str) -> Option<Box<dyn ScoringRule>> {
+    match spec.split_once(':') {
+        None if spec == "doubling" => Some(Box::new(Doubling)),
+        None if spec == "linear" => Some(Box::new(Linear)),
+        None if spec == "fibonacci" => Some(Box::new(Fibonacci)),
+        Some(("cascade", multiplier)) => Some(Box::new(Cascade {
+            multiplier: multiplier.parse().ok()?,
+        })),
+        _ => None,
+    }
 }
 
 struct Card {
-    winning: Vec<usize>,
+    id: usize,
+    winning: HashSet<usize>,
     played: Vec<usize>,
 }
 
 impl Card {
+    /// Counts the played numbers that are winning ones, a played number given
+    /// twice counting twice.
     fn matches(&self) -> usize {
         self.played
             .iter()
             .filter(|p| self.winning.contains(p))
             .count()
     }
+}
 
-    fn points(&self) -> usize {
-        match self.matches() {
-            0 => 0,
-            matches => 1 << matches - 1,
+#[derive(PartialEq, Debug)]
+enum CardIssue {
+    OutOfOrder {
+        line: usize,
+        id: usize,
+    },
+    DuplicateId {
+        line: usize,
+        id: usize,
+    },
+    MissingId(usize),
+    DuplicateNumber {
+        line: usize,
+        side: Side,
+        number: usize,
+    },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Side {
+    Winning,
+    Played,
+}
+
+impl CardIssue {
+    /// Out of order cards are still scored by id and repeated played numbers
+    /// each count as a match, every other issue makes the table ambiguous.
+    fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            CardIssue::OutOfOrder { .. }
+                | CardIssue::DuplicateNumber {
+                    side: Side::Played,
+                    ..
+                }
+        )
+    }
+}
+
+impl std::fmt::Display for CardIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CardIssue::OutOfOrder { line, id } => {
+                write!(f, "line {}: card {} is out of order", line, id)
+            }
+            CardIssue::DuplicateId { line, id } => {
+                write!(f, "line {}: card {} appears more than once", line, id)
+            }
+            CardIssue::MissingId(id) => write!(f, "card {} is missing", id),
+            CardIssue::DuplicateNumber { line, side, number } => write!(
+                f,
+                "line {}: {} appears more than once among the {} numbers",
+                line,
+                number,
+                match side {
+                    Side::Winning => "winning",
+                    Side::Played => "played",
+                }
+            ),
         }
     }
 }
 
-fn parse_cards(input: &str) -> Vec<Card> {
-    input
-        .lines()
-        .map(|l| {
-            let (left, right) = l.split_once(":").unwrap().1.split_once("|").unwrap();
+fn parse_cards(input: &str) -> Result<Vec<Card>, Vec<CardIssue>> {
+    let _span = aoc::span("parse");
+    let issues = check_cards(input);
 
-            Card {
-                winning: left
-                    .split_whitespace()
-                    .map(|n| n.parse::<usize>().unwrap())
-                    .collect_vec(),
-                played: right
-                    .split_whitespace()
-                    .map(|n| n.parse::<usize>().unwrap())
-                    .collect_vec(),
+    if issues.iter().any(CardIssue::is_fatal) {
+        return Err(issues.into_iter().filter(CardIssue::is_fatal).collect());
+    }
+
+    Ok(parse_lines(input)
+        .map(|(id, winning, played)| Card {
+            id,
+            winning: winning.into_iter().collect(),
+            played,
+        })
+        .collect())
+}
+
+/// Reports cards that are out of order, repeated or missing from `1` up to the
+/// highest id, and numbers repeated within one side of a card.
+fn check_cards(input: &str) -> Vec<CardIssue> {
+    let mut issues = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for (i, (id, winning, played)) in parse_lines(input).enumerate() {
+        let line = i + 1;
+
+        if !seen.insert(id) {
+            issues.push(CardIssue::DuplicateId { line, id });
+        } else if seen.last() != Some(&id) {
+            issues.push(CardIssue::OutOfOrder { line, id });
+        }
+
+        for (side, numbers) in [(Side::Winning, winning), (Side::Played, played)] {
+            for number in numbers.into_iter().duplicates() {
+                issues.push(CardIssue::DuplicateNumber { line, side, number });
             }
+        }
+    }
+
+    let last = seen.last().copied().unwrap_or(0);
+    issues.extend(
+        (1..=last)
+            .filter(|id| !seen.contains(id))
+            .map(CardIssue::MissingId),
+    );
+
+    issues
+}
+
+/// Valid cards with `winning` and `played` distinct numbers each, of which
+/// at most ten match, so that points do not overflow however long cards get.
+fn generate_cards(cards: usize, winning: usize, played: usize, rng: &mut Rng) -> String {
+    let mut values = (1..=2 * (winning + played)).collect_vec();
+
+    (1..=cards)
+        .map(|id| {
+            rng.shuffle(&mut values);
+
+            let matches = rng.below(winning.min(played).min(10) as u64 + 1) as usize;
+            let (drawn, rest) = values.split_at(winning);
+            let played = rest[..played - matches].iter().chain(&drawn[..matches]);
+
+            format!(
+                "Card {}: {} | {}\n",
+                id,
+                drawn.iter().join(" "),
+                played.format(" ")
+            )
         })
         .collect()
 }
 
+fn parse_lines(input: &str) -> impl Iterator<Item = (usize, Vec<usize>, Vec<usize>)> + '_ {
+    input.lines().map(|l| {
+        let (header, numbers) = l.split_once(':').unwrap();
+        let (left, right) = numbers.split_once('|').unwrap();
+        let parse = |side: &str| {
+            side.split_whitespace()
+                .map(|n| n.parse::<usize>().unwrap())
+                .collect_vec()
+        };
+
+        (
+            header.split_whitespace().nth(1).unwrap().parse().unwrap(),
+            parse(left),
+            parse(right),
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     const EXAMPLE_GAME: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
@@ -89,4 +420,174 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
     fn part_2_test() {
         assert_eq!("30", part_2(EXAMPLE_GAME));
     }
+
+    fn generate_card(
+        id: usize,
+        winning: impl Iterator<Item = usize>,
+        played: impl Iterator<Item = usize>,
+    ) -> String {
+        format!(
+            "Card {}: {} | {}",
+            id,
+            winning.map(|n| n.to_string()).join(" "),
+            played.map(|n| n.to_string()).join(" ")
+        )
+    }
+
+    #[rstest]
+    #[case(0, "0")]
+    #[case(1, "1")]
+    #[case(10, "512")]
+    #[case(64, "9223372036854775808")]
+    #[case(65, "18446744073709551616")]
+    #[case(5000, &(BigUint::from(1u8) << 4999usize).to_string())]
+    fn large_card_test(#[case] matches: usize, #[case] expected_points: &str) {
+        let input = generate_card(1, 0..5000, (5000 - matches)..(10000 - matches));
+
+        assert_eq!(part_1(&input), expected_points);
+        assert_eq!(part_2(&input), "1");
+    }
+
+    #[test]
+    fn cascade_past_end_test() {
+        let input = (1..=100)
+            .map(|id| generate_card(id, (0..1000).map(|n| n * 2), (0..1000).map(|n| n * 3)))
+            .join("\n");
+
+        assert_eq!(cards(&input)[0].matches(), 334);
+        // Every card wins a copy of all the cards after it, so card `k` ends
+        // up with 2^(k - 1) copies.
+        assert_eq!(
+            part_1(&input),
+            (BigUint::from(100u8) << 333usize).to_string()
+        );
+        assert_eq!(
+            part_2(&input),
+            ((BigUint::from(1u8) << 100usize) - 1u8).to_string()
+        );
+    }
+
+    #[test]
+    fn repeated_played_number_test() {
+        let input = "Card 1: 1 2 | 2 3 2";
+
+        assert_eq!(
+            check_cards(input),
+            vec![CardIssue::DuplicateNumber {
+                line: 1,
+                side: Side::Played,
+                number: 2
+            }]
+        );
+        assert_eq!(part_1(input), "2");
+        assert_eq!(part_2(input), "1");
+    }
+
+    #[rstest]
+    #[case(&[(1, 4), (2, 2), (3, 2), (4, 1), (5, 0), (6, 0)], &[1, 2, 4, 8, 14, 1])]
+    #[case(&[(1, 3), (2, 2), (3, 1)], &[1, 2, 4])]
+    #[case(&[(4, 1), (2, 2), (3, 0), (1, 1)], &[1, 2, 3, 3])]
+    #[case(&[], &[])]
+    fn count_copies_test(#[case] card_matches: &[(usize, usize)], #[case] expected: &[usize]) {
+        assert_eq!(
+            count_copies(&card_matches.iter().copied().collect(), &Doubling)
+                .into_values()
+                .collect_vec(),
+            expected.iter().map(|c| BigUint::from(*c)).collect_vec()
+        );
+    }
+
+    #[test]
+    fn shuffled_cards_test() {
+        let shuffled = EXAMPLE_GAME.lines().rev().join("\n");
+
+        assert_eq!(part_1(&shuffled), "13");
+        assert_eq!(part_2(&shuffled), "30");
+        assert_eq!(
+            check_cards(&shuffled),
+            (1..=5)
+                .map(|i| CardIssue::OutOfOrder {
+                    line: i + 1,
+                    id: 6 - i
+                })
+                .collect_vec()
+        );
+    }
+
+    #[rstest]
+    #[case("Card 1: 1 2 | 3\nCard 3: 1 | 1", vec![CardIssue::MissingId(2)])]
+    #[case("Card 2: 1 2 | 3", vec![CardIssue::MissingId(1)])]
+    #[case("Card 1: 1 2 | 3\nCard 1: 1 | 1", vec![CardIssue::DuplicateId { line: 2, id: 1 }])]
+    #[case("Card 1: 1 2 1 | 3 3 3", vec![
+        CardIssue::DuplicateNumber { line: 1, side: Side::Winning, number: 1 },
+        CardIssue::DuplicateNumber { line: 1, side: Side::Played, number: 3 },
+    ])]
+    #[case("Card 2: 1 | 1\nCard 1: 1 | 1\nCard 3: 1 | 1", vec![CardIssue::OutOfOrder { line: 2, id: 1 }])]
+    fn check_cards_test(#[case] input: &str, #[case] expected: Vec<CardIssue>) {
+        assert_eq!(check_cards(input), expected);
+        assert_eq!(
+            parse_cards(input).is_ok(),
+            expected.iter().all(|i| !i.is_fatal())
+        );
+    }
+
+    #[rstest]
+    #[case("doubling", [0, 1, 2, 4, 8, 16].into())]
+    #[case("linear", [0, 1, 2, 3, 4, 5].into())]
+    #[case("fibonacci", [0, 1, 2, 3, 5, 8].into())]
+    #[case("cascade:3", [0, 1, 2, 4, 8, 16].into())]
+    fn points_test(#[case] spec: &str, #[case] expected: Vec<usize>) {
+        let rule = scoring_rule(spec).unwrap();
+
+        assert_eq!(
+            (0..expected.len()).map(|m| rule.points(m)).collect_vec(),
+            expected.into_iter().map(BigUint::from).collect_vec()
+        );
+    }
+
+    #[rstest]
+    #[case("doubling", 13, 30)]
+    #[case("linear", 4 + 2 + 2 + 1, 30)]
+    #[case("fibonacci", 5 + 2 + 2 + 1, 30)]
+    #[case("cascade:1", 13, 30)]
+    #[case("cascade:2", 13, 1 + 3 + 9 + 27 + 75 + 1)]
+    fn scoring_rule_test(
+        #[case] spec: &str,
+        #[case] expected_points: usize,
+        #[case] expected_cards: usize,
+    ) {
+        let cards = cards(EXAMPLE_GAME);
+        let rule = scoring_rule(spec).unwrap();
+
+        assert_eq!(
+            total_points(&cards, rule.as_ref()),
+            BigUint::from(expected_points)
+        );
+        assert_eq!(
+            total_cards(&cards, rule.as_ref()),
+            BigUint::from(expected_cards)
+        );
+    }
+
+    #[test]
+    fn fibonacci_past_usize_test() {
+        assert_eq!(
+            Fibonacci.points(92),
+            BigUint::from(12200160415121876738usize)
+        );
+        assert_eq!(
+            Fibonacci.points(93),
+            "19740274219868223167".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_cards_test() {
+        let input = generate_cards(30, 40, 60, &mut Rng::new(4));
+
+        assert!(check_cards(&input).is_empty());
+        assert!(cards(&input)
+            .iter()
+            .all(|c| (c.winning.len(), c.played.len()) == (40, 60) && c.matches() <= 10));
+    }
 }
@@ -1,10 +1,203 @@
 mod aoc;
+#[path = "day05/bench.rs"]
+mod bench;
+mod interval;
+#[path = "day05/trace.rs"]
+mod trace;
+#[path = "day05/validate.rs"]
+mod validate;
 
-use aoc::Solver;
+use aoc::{Dimension, Solver};
+use interval::{Interval, IntervalSet, MaybeOverlap};
 use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+type Range = Interval<u64>;
 
 fn main() {
-    ("day05.txt", [part_1 as fn(&str) -> String, part_2]).solve();
+    let args = aoc::args();
+
+    match args.first().map(String::as_str) {
+        Some("composed") => (
+            "day05.txt",
+            [part_1_composed as fn(&str) -> String, part_2_composed],
+        )
+            .solve(),
+        Some("cross-check") => (
+            "day05.txt",
+            [
+                &[
+                    ("by-stage", part_1 as fn(&str) -> String),
+                    ("composed", part_1_composed),
+                ][..],
+                &[("by-stage", part_2), ("composed", part_2_composed)],
+            ],
+        )
+            .solve(),
+        Some("bench") => {
+            let almanac = bench::generate(
+                aoc::option(&args, "seed").map_or(2023, |v| v.parse().unwrap()),
+                aoc::option(&args, "seeds").map_or(10_000, |v| v.parse().unwrap()),
+                aoc::option(&args, "entries").map_or(40, |v| v.parse().unwrap()),
+            );
+
+            let runs = aoc::option(&args, "runs").map_or(5, |v| v.parse().unwrap());
+
+            if !bench::compare(&almanac, runs) {
+                std::process::exit(1);
+            }
+        }
+        Some("closest") => {
+            let almanac = almanac(&args);
+            let (location, seeds) = almanac.closest_seeds().unwrap();
+
+            println!(
+                "location {} is reached from seeds {}",
+                location,
+                seeds.iter().join(", ")
+            );
+        }
+        Some("convert") => {
+            let arguments = positional(&args);
+            let values = arguments
+                .get(2..)
+                .unwrap_or_default()
+                .iter()
+                .map(|v| v.parse().ok().and_then(|v| Range::new(v, v)))
+                .collect::<Option<IntervalSet<u64>>>()
+                .filter(|v| !v.is_empty());
+
+            let (Some(from), Some(to), Some(values)) =
+                (arguments.first(), arguments.get(1), values)
+            else {
+                usage("convert FROM TO VALUE... [--part=2]");
+            };
+
+            match almanac(&args).convert(from, to, &values) {
+                Ok(converted) => println!("{}", converted.iter().join(", ")),
+                Err(e) => println!("{}", e),
+            }
+        }
+        Some("validate") => {
+            let strictness = match aoc::flag(&args, "strict") {
+                true => validate::Strictness::Strict,
+                false => validate::Strictness::Warn,
+            };
+            let findings = validate::validate(&aoc::read_input("day05.txt"));
+
+            for finding in findings.iter() {
+                println!("{:?}: {}", finding.severity(strictness), finding);
+            }
+
+            if findings
+                .iter()
+                .any(|f| f.severity(strictness) == validate::Severity::Error)
+            {
+                std::process::exit(1);
+            }
+        }
+        Some("normalize") => {
+            let seed_writer = match aoc::option(&args, "part") {
+                Some("2") => Almanac::part_2_seed_writer,
+                _ => Almanac::part_1_seed_writer,
+            };
+
+            print!("{}", almanac(&args).normalize().write(seed_writer));
+        }
+        Some("trace") => {
+            let almanac = almanac(&args);
+            let traces = trace::trace(&almanac);
+
+            if let Some(path) = aoc::option(&args, "csv") {
+                std::fs::write(path, trace::to_csv(&almanac, &traces)).unwrap();
+            }
+
+            if let Some(path) = aoc::option(&args, "svg") {
+                std::fs::write(path, trace::to_svg(&almanac, &traces)).unwrap();
+            }
+
+            for t in traces.iter() {
+                println!("seed {}", t.seed);
+
+                for hop in t.hops.iter() {
+                    println!(
+                        "{:indent$}{} {} -> {} {}{}",
+                        "",
+                        almanac.categories[hop.stage],
+                        hop.from,
+                        almanac.categories[hop.stage + 1],
+                        hop.to,
+                        hop.entry
+                            .map_or(String::new(), |e| format!(" (entry {})", e)),
+                        indent = 2 * (hop.stage + 1)
+                    );
+                }
+            }
+        }
+        Some("seeds-in") => {
+            let locations = match positional(&args)[..] {
+                [start, end] => start
+                    .parse()
+                    .ok()
+                    .zip(end.parse().ok())
+                    .and_then(|(start, end)| Range::new(start, end)),
+                _ => None,
+            };
+
+            let Some(locations) = locations else {
+                usage("seeds-in START END [--part=2]");
+            };
+
+            for seeds in almanac(&args).seeds_for_locations(locations) {
+                println!("{}", seeds);
+            }
+        }
+        Some("scaling") => aoc::scaling(
+            "day05",
+            &[
+                Dimension {
+                    name: "seeds",
+                    generate: |seeds, rng| {
+                        bench::generate(rng.next(), seeds, 40).write(Almanac::part_2_seed_writer)
+                    },
+                    start: 250,
+                },
+                Dimension {
+                    name: "entries",
+                    generate: |entries, rng| {
+                        bench::generate(rng.next(), 100, entries).write(Almanac::part_2_seed_writer)
+                    },
+                    start: 100,
+                },
+            ],
+            [part_1, part_2],
+        ),
+        _ => ("day05.txt", [part_1, part_2]).solve(),
+    }
+}
+
+/// The arguments following the subcommand that are not `--` options.
+fn positional(args: &[String]) -> Vec<&str> {
+    args.iter()
+        .skip(1)
+        .map(String::as_str)
+        .filter(|a| !a.starts_with("--"))
+        .collect()
+}
+
+fn usage(subcommand: &str) -> ! {
+    println!("usage: day05 {}", subcommand);
+    std::process::exit(1);
+}
+
+/// Reads the real almanac, with seed ranges when `--part=2` is given.
+fn almanac(args: &[String]) -> Almanac {
+    let seed_parser = match aoc::option(args, "part") {
+        Some("2") => Almanac::part_2_seed_parser,
+        _ => Almanac::part_1_seed_parser,
+    };
+
+    Almanac::from_input(&aoc::read_input("day05.txt"), seed_parser)
 }
 
 fn part_1(input: &str) -> String {
@@ -15,186 +208,663 @@ fn part_2(input: &str) -> String {
     solve_with(input, Almanac::part_2_seed_parser)
 }
 
-fn solve_with(input: &str, seed_parser: fn(&str) -> Vec<Range>) -> String {
-    Almanac::from_input(input, seed_parser)
-        .map_seeds()
-        .iter()
-        .map(|m| m.start)
+fn part_1_composed(input: &str) -> String {
+    solve_composed_with(input, Almanac::part_1_seed_parser)
+}
+
+fn part_2_composed(input: &str) -> String {
+    solve_composed_with(input, Almanac::part_2_seed_parser)
+}
+
+fn solve_with(input: &str, seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>) -> String {
+    let almanac = Almanac::from_input(input, seed_parser);
+
+    for (i, seeds) in almanac.seeds.iter().enumerate() {
+        aoc::explain("seed range", i + 1, || {
+            format!(
+                "{} → {}",
+                seeds,
+                almanac.map_ranges((*seeds).into()).iter().join(", ")
+            )
+        });
+    }
+
+    almanac.map_seeds().min().unwrap().to_string()
+}
+
+/// Solves through the stages composed into a single map, which pays off
+/// once there are many more seed ranges than entries.
+fn solve_composed_with(
+    input: &str,
+    seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>,
+) -> String {
+    let almanac = Almanac::from_input(input, seed_parser);
+
+    almanac
+        .compose()
+        .map_set(&almanac.seeds.iter().copied().collect())
         .min()
         .unwrap()
         .to_string()
 }
 
+#[derive(Clone, PartialEq, Debug)]
 struct Almanac {
     seeds: Vec<Range>,
+    /// Category names in chain order, `conversion_stages[i]` converting from
+    /// `categories[i]` to `categories[i + 1]`.
+    categories: Vec<String>,
     conversion_stages: Vec<Vec<Map>>,
 }
 
-struct Map {
-    source: Range,
-    destination: Range,
+#[derive(PartialEq, Debug)]
+enum AlmanacError {
+    MalformedHeader(String),
+    Branching(String),
+    Merging(String),
+    Cycle(Vec<String>),
+    Unreachable(String),
+    UnknownCategory(String),
+    Overflow(String),
+}
+
+impl std::fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlmanacError::MalformedHeader(header) => write!(f, "malformed map header '{}'", header),
+            AlmanacError::Branching(category) => {
+                write!(f, "{} is converted by more than one map", category)
+            }
+            AlmanacError::Merging(category) => {
+                write!(f, "more than one map converts into {}", category)
+            }
+            AlmanacError::Cycle(categories) => {
+                write!(f, "maps form a cycle through {}", categories.join(", "))
+            }
+            AlmanacError::Unreachable(category) => {
+                write!(f, "{} cannot be reached from seed", category)
+            }
+            AlmanacError::UnknownCategory(category) => write!(f, "unknown category {}", category),
+            AlmanacError::Overflow(entry) => write!(f, "'{}' runs past {}", entry, u64::MAX),
+        }
+    }
+}
+
+/// Moves `value` from its offset past `from` to the same offset past `to`,
+/// or `None` when `value` lies before `from` or the result runs past
+/// `u64::MAX`.
+fn shift(value: u64, from: u64, to: u64) -> Option<u64> {
+    value.checked_sub(from)?.checked_add(to)
+}
+
+/// The `len` values from `start`, `None` when there are none, rejecting the
+/// ranges running past `u64::MAX` that `text` describes.
+fn range_from_len(start: u64, len: u64, text: &str) -> Result<Option<Range>, AlmanacError> {
+    match len {
+        0 => Ok(None),
+        _ => Range::from_len(start, len)
+            .map(Some)
+            .ok_or_else(|| AlmanacError::Overflow(text.to_string())),
+    }
+}
+
+/// An entry of a conversion stage, its `destination` as long as its
+/// `source`, which `Almanac::parse` makes sure of.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Map {
+    pub source: Range,
+    pub destination: Range,
 }
 
 impl Map {
-    fn translate(&self, range: &Range) -> MaybeOverlap {
-        let maybe_overlap = self.source.test_overlap(range);
+    fn apply(&self, value: u64) -> u64 {
+        shift(value, self.source.start(), self.destination.start())
+            .expect("map destination overflows")
+    }
 
-        let mapped_range = maybe_overlap.overlap.and_then(|overlap| {
-            let start = overlap.start - self.source.start + self.destination.start;
-            let end = start + overlap.len;
-            let len = end - start;
+    fn unapply(&self, value: u64) -> u64 {
+        shift(value, self.destination.start(), self.source.start()).expect("map source overflows")
+    }
 
-            Some(Range::new(start, len))
-        });
+    fn translate(&self, range: &Range) -> MaybeOverlap<u64> {
+        let maybe_overlap = self.source.test_overlap(range);
 
         MaybeOverlap {
             left: maybe_overlap.left,
-            overlap: mapped_range,
+            overlap: maybe_overlap
+                .overlap
+                .map(|o| Range::new(self.apply(o.start()), self.apply(o.end())).unwrap()),
             right: maybe_overlap.right,
         }
     }
+
+    /// The source values this map sends into `range`.
+    fn untranslate(&self, range: &Range) -> Option<Range> {
+        let overlap = self.destination.intersection(range)?;
+
+        Range::new(self.unapply(overlap.start()), self.unapply(overlap.end()))
+    }
 }
 
-#[derive(PartialEq, Debug)]
-struct MaybeOverlap {
-    left: Option<Range>,
-    overlap: Option<Range>,
-    right: Option<Range>,
+/// A function over every `u64` made of pieces that each shift a range of
+/// inputs by a constant, sorted by input and merged where they line up.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
-struct Range {
-    start: usize,
-    end: usize,
-    len: usize,
+pub struct Piece {
+    pub source: Range,
+    pub destination: u64,
 }
 
-impl Range {
-    fn new(start: usize, len: usize) -> Self {
-        Self {
-            start,
-            end: start + len,
-            len,
+impl Piece {
+    fn apply(&self, value: u64) -> u64 {
+        shift(value, self.source.start(), self.destination).expect("piece image overflows")
+    }
+
+    fn unapply(&self, value: u64) -> u64 {
+        shift(value, self.destination, self.source.start()).expect("piece source overflows")
+    }
+
+    fn image(&self) -> Range {
+        Range::new(
+            self.apply(self.source.start()),
+            self.apply(self.source.end()),
+        )
+        .unwrap()
+    }
+
+    /// The piece taking the inputs that land on `image` to `destination`.
+    fn restrict(&self, image: &Range, destination: u64) -> Piece {
+        Piece {
+            source: Range::new(self.unapply(image.start()), self.unapply(image.end())).unwrap(),
+            destination,
         }
     }
+}
 
-    fn try_from(start: usize, end: usize) -> Option<Self> {
-        match start < end {
-            true => Some(Self {
-                start,
-                end,
-                len: end - start,
-            }),
-            false => None,
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        Self {
+            pieces: vec![Piece {
+                source: Range::new(0, u64::MAX).unwrap(),
+                destination: 0,
+            }],
         }
     }
 
-    fn test_overlap(&self, rhs: &Range) -> MaybeOverlap {
-        let left = Range::try_from(self.start.min(rhs.start), self.start.min(rhs.end));
-        let overlap = Range::try_from(self.start.max(rhs.start), self.end.min(rhs.end));
-        let right = Range::try_from(self.end.max(rhs.start), self.end.max(rhs.end));
+    pub fn pieces(&self) -> &[Piece] {
+        &self.pieces
+    }
+
+    /// Composes this map with a conversion stage applied after it.
+    pub fn then(&self, stage: &[Map]) -> Self {
+        let mut pieces = Vec::new();
 
-        MaybeOverlap {
-            left,
-            overlap,
-            right,
+        for piece in self.pieces.iter() {
+            let mut unmapped = vec![piece.image()];
+
+            for map in stage.iter() {
+                let mut remaining = Vec::new();
+
+                for image in unmapped {
+                    let maybe_overlap = map.source.test_overlap(&image);
+
+                    if let Some(hit) = maybe_overlap.overlap {
+                        pieces.push(piece.restrict(&hit, map.apply(hit.start())));
+                    }
+
+                    remaining.extend(maybe_overlap.left);
+                    remaining.extend(maybe_overlap.right);
+                }
+
+                unmapped = remaining;
+            }
+
+            pieces.extend(
+                unmapped
+                    .iter()
+                    .map(|image| piece.restrict(image, image.start())),
+            );
         }
+
+        Self::from_pieces(pieces)
+    }
+
+    fn from_pieces(mut pieces: Vec<Piece>) -> Self {
+        pieces.sort_by_key(|p| p.source.start());
+
+        let pieces = pieces
+            .into_iter()
+            .coalesce(|a, b| {
+                let contiguous = a.source.end().checked_add(1) == Some(b.source.start())
+                    && a.image().end().checked_add(1) == Some(b.destination);
+
+                match contiguous {
+                    true => Ok(Piece {
+                        source: Range::new(a.source.start(), b.source.end()).unwrap(),
+                        destination: a.destination,
+                    }),
+                    false => Err((a, b)),
+                }
+            })
+            .collect();
+
+        Self { pieces }
+    }
+
+    fn piece_index(&self, value: u64) -> usize {
+        self.pieces.partition_point(|p| p.source.end() < value)
+    }
+
+    pub fn lookup(&self, value: u64) -> u64 {
+        self.pieces[self.piece_index(value)].apply(value)
+    }
+
+    /// Maps every value of `range`, splitting it only where pieces meet.
+    pub fn map_range(&self, range: &Range) -> Vec<Range> {
+        self.pieces[self.piece_index(range.start())..]
+            .iter()
+            .map_while(|p| {
+                let hit = p.source.intersection(range)?;
+
+                Some(Range::new(p.apply(hit.start()), p.apply(hit.end())).unwrap())
+            })
+            .collect()
+    }
+
+    pub fn map_set(&self, set: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let _span = aoc::span("map_set");
+
+        set.iter().flat_map(|r| self.map_range(r)).collect()
     }
 }
 
 impl Almanac {
-    fn part_1_seed_parser(line: &str) -> Vec<Range> {
-        line.split_whitespace()
-            .map(|s| s.parse::<usize>().unwrap())
-            .map(|s| Range::new(s, 1))
-            .collect()
+    fn part_1_seed_parser(line: &str) -> Result<Vec<Range>, AlmanacError> {
+        Ok(line
+            .split_whitespace()
+            .map(|s| s.parse::<u64>().unwrap())
+            .map(|s| Range::new(s, s).unwrap())
+            .collect())
     }
 
-    fn part_2_seed_parser(line: &str) -> Vec<Range> {
+    fn part_2_seed_parser(line: &str) -> Result<Vec<Range>, AlmanacError> {
         line.split_whitespace()
-            .map(|s| s.parse::<usize>().unwrap())
-            .chunks(2)
-            .into_iter()
-            .map(|ns| ns.collect_vec())
-            .map(|ns| Range::new(ns[0], ns[1]))
+            .tuples()
+            .map(|(start, len)| {
+                range_from_len(
+                    start.parse().unwrap(),
+                    len.parse().unwrap(),
+                    &format!("{} {}", start, len),
+                )
+            })
+            .flatten_ok()
             .collect()
     }
 
-    fn from_input(input: &str, seed_parser: fn(&str) -> Vec<Range>) -> Self {
-        let seeds = seed_parser(input.lines().nth(0).unwrap().split_once(":").unwrap().1);
+    fn part_1_seed_writer(seeds: &[Range]) -> String {
+        seeds.iter().flat_map(|r| r.start()..=r.end()).join(" ")
+    }
+
+    fn part_2_seed_writer(seeds: &[Range]) -> String {
+        seeds
+            .iter()
+            .map(|r| format!("{} {}", r.start(), r.end() - r.start() + 1))
+            .join(" ")
+    }
+
+    fn from_input(input: &str, seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>) -> Self {
+        Self::parse(input, seed_parser).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Parses the almanac, chaining its maps by category name from `seed`
+    /// regardless of the order they appear in.
+    fn parse(
+        input: &str,
+        seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>,
+    ) -> Result<Self, AlmanacError> {
+        let _span = aoc::span("parse");
+        let seeds = seed_parser(input.lines().next().unwrap().split_once(':').unwrap().1)?;
 
-        let conversion_stages = input
+        let stages = input
             .split("\n\n")
             .skip(1)
             .map(|m| {
-                m.lines()
+                let header = m.lines().next().unwrap_or_default();
+                let (source, destination) = header
+                    .strip_suffix(" map:")
+                    .and_then(|h| h.split_once("-to-"))
+                    .ok_or_else(|| AlmanacError::MalformedHeader(header.to_string()))?;
+
+                let maps = m
+                    .lines()
                     .skip(1)
                     .map(|l| {
-                        l.split_whitespace()
-                            .map(|n| n.parse::<usize>().unwrap())
-                            .collect_vec()
+                        let ns = l
+                            .split_whitespace()
+                            .map(|n| n.parse::<u64>().unwrap())
+                            .collect_vec();
+                        let source = range_from_len(ns[1], ns[2], l)?;
+                        let destination = range_from_len(ns[0], ns[2], l)?;
+
+                        Ok(source.zip(destination).map(|(source, destination)| Map {
+                            source,
+                            destination,
+                        }))
                     })
-                    .map(|ns| Map {
-                        source: Range::new(ns[1], ns[2]),
-                        destination: Range::new(ns[0], ns[2]),
+                    .flatten_ok()
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((source.to_string(), destination.to_string(), maps))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let order = Self::sort_stages(
+            &stages
+                .iter()
+                .map(|(s, d, _)| (s.as_str(), d.as_str()))
+                .collect_vec(),
+        )?;
+
+        let mut stages = stages.into_iter().map(Some).collect_vec();
+        let mut categories = vec!["seed".to_string()];
+        let mut conversion_stages = Vec::new();
+
+        for i in order {
+            let (_, destination, maps) = stages[i].take().unwrap();
+            categories.push(destination);
+            conversion_stages.push(maps);
+        }
+
+        Ok(Self {
+            seeds,
+            categories,
+            conversion_stages,
+        })
+    }
+
+    /// Writes the almanac back in the puzzle format, with its maps in chain
+    /// order, so that parsing the text with the matching seed parser gives
+    /// the same almanac.
+    fn write(&self, seed_writer: fn(&[Range]) -> String) -> String {
+        let stages = self
+            .categories
+            .iter()
+            .tuple_windows()
+            .zip(self.conversion_stages.iter())
+            .map(|((source, destination), stage)| {
+                let entries = stage.iter().map(|m| {
+                    format!(
+                        "\n{} {} {}",
+                        m.destination.start(),
+                        m.source.start(),
+                        m.source.end() - m.source.start() + 1
+                    )
+                });
+
+                format!(
+                    "\n{}-to-{} map:{}\n",
+                    source,
+                    destination,
+                    entries.format("")
+                )
+            });
+
+        format!("seeds: {}\n{}", seed_writer(&self.seeds), stages.format(""))
+    }
+
+    /// Rewrites every stage as the same function with its entries sorted by
+    /// source, adjacent entries sharing an offset merged and entries that
+    /// map values to themselves dropped. Overlapping entries are resolved the
+    /// way `map_stage` resolves them, the first one winning.
+    fn normalize(&self) -> Self {
+        let conversion_stages = self
+            .conversion_stages
+            .iter()
+            .map(|stage| {
+                PiecewiseMap::identity()
+                    .then(stage)
+                    .pieces()
+                    .iter()
+                    .filter(|p| p.source.start() != p.destination)
+                    .map(|p| Map {
+                        source: p.source,
+                        destination: p.image(),
                     })
-                    .collect_vec()
+                    .collect()
             })
-            .collect_vec();
+            .collect();
 
         Self {
-            seeds,
+            seeds: self.seeds.clone(),
+            categories: self.categories.clone(),
             conversion_stages,
         }
     }
 
-    fn map_seeds(&self) -> Vec<Range> {
-        self.seeds
-            .iter()
-            .map(|s| {
-                let mut seed_ranges = Vec::from_iter([*s]);
+    /// Orders the `(source, destination)` edges into a single chain starting
+    /// at `seed`, rejecting branches, merges, cycles and unreachable maps.
+    fn sort_stages(edges: &[(&str, &str)]) -> Result<Vec<usize>, AlmanacError> {
+        let mut outgoing = HashMap::new();
+        let mut incoming = HashSet::new();
 
-                for stage in self.conversion_stages.iter() {
-                    let mut stage_output = Vec::new();
+        for (i, (source, destination)) in edges.iter().enumerate() {
+            if outgoing.insert(*source, i).is_some() {
+                return Err(AlmanacError::Branching(source.to_string()));
+            }
 
-                    'seed: while let Some(seed) = seed_ranges.pop() {
-                        for map in stage.iter() {
-                            let maybe_translated = map.translate(&seed);
+            if !incoming.insert(*destination) {
+                return Err(AlmanacError::Merging(destination.to_string()));
+            }
+        }
 
-                            if let Some(translated) = maybe_translated.overlap {
-                                stage_output.push(translated);
+        if incoming.contains("seed") {
+            let mut cycle = vec!["seed".to_string()];
 
-                                if let Some(left) = maybe_translated.left {
-                                    seed_ranges.push(left);
-                                }
+            while let Some(i) = outgoing.get(cycle.last().unwrap().as_str()) {
+                match edges[*i].1 {
+                    "seed" => return Err(AlmanacError::Cycle(cycle)),
+                    next => cycle.push(next.to_string()),
+                }
+            }
+        }
 
-                                if let Some(right) = maybe_translated.right {
-                                    stage_output.push(right);
-                                }
+        let mut order = Vec::new();
+        let mut category = "seed";
 
-                                continue 'seed;
-                            }
-                        }
+        while let Some(i) = outgoing.remove(category) {
+            order.push(i);
+            category = edges[i].1;
+        }
+
+        match outgoing.iter().min_by_key(|(_, i)| **i) {
+            None => Ok(order),
+            Some((source, i)) => {
+                let mut cycle = vec![source.to_string()];
+                let mut next = edges[*i].1;
 
-                        stage_output.push(seed);
+                while let Some(j) = outgoing.get(next) {
+                    if next == *source {
+                        return Err(AlmanacError::Cycle(cycle));
                     }
 
-                    seed_ranges.append(&mut stage_output);
+                    cycle.push(next.to_string());
+                    next = edges[*j].1;
                 }
 
-                seed_ranges
+                Err(AlmanacError::Unreachable(source.to_string()))
+            }
+        }
+    }
+
+    fn category_name(&self, index: usize) -> String {
+        self.categories.get(index).cloned().unwrap_or_default()
+    }
+
+    fn category_index(&self, category: &str) -> Result<usize, AlmanacError> {
+        self.categories
+            .iter()
+            .position(|c| c == category)
+            .ok_or_else(|| AlmanacError::UnknownCategory(category.to_string()))
+    }
+
+    /// Converts `values` from one category to another, going backwards
+    /// through the maps when `to` comes before `from` in the chain.
+    fn convert(
+        &self,
+        from: &str,
+        to: &str,
+        values: &IntervalSet<u64>,
+    ) -> Result<IntervalSet<u64>, AlmanacError> {
+        let (from, to) = (self.category_index(from)?, self.category_index(to)?);
+
+        Ok(match from <= to {
+            true => self.conversion_stages[from..to]
+                .iter()
+                .fold(values.clone(), |v, stage| Self::map_stage(stage, &v)),
+            false => self.conversion_stages[to..from]
+                .iter()
+                .rev()
+                .fold(values.clone(), |v, stage| Self::unmap_stage(stage, &v)),
+        })
+    }
+
+    fn compose(&self) -> PiecewiseMap {
+        self.conversion_stages.iter().enumerate().fold(
+            PiecewiseMap::identity(),
+            |map, (i, stage)| {
+                let _span = aoc::span_with("compose", || self.category_name(i + 1));
+                map.then(stage)
+            },
+        )
+    }
+
+    /// Maps the seed ranges on as many threads as there are cores.
+    fn map_seeds(&self) -> IntervalSet<u64> {
+        self.map_seeds_in(std::thread::available_parallelism().map_or(1, usize::from))
+    }
+
+    /// Splits the seed ranges into `threads` chunks, at least one, mapped on
+    /// a thread each, falling back to `map_seeds_serial` when there is a
+    /// single chunk. Chunks are merged in seed order, although the union
+    /// does not depend on it.
+    fn map_seeds_in(&self, threads: usize) -> IntervalSet<u64> {
+        let chunk_size = self.seeds.len().div_ceil(threads.max(1)).max(1);
+
+        if chunk_size >= self.seeds.len() {
+            return self.map_seeds_serial();
+        }
+
+        std::thread::scope(|scope| {
+            let chunks = self
+                .seeds
+                .chunks(chunk_size)
+                .map(|seeds| scope.spawn(|| self.map_ranges(seeds.iter().copied().collect())))
+                .collect_vec();
+
+            chunks
+                .into_iter()
+                .map(|c| c.join().unwrap())
+                .fold(IntervalSet::new(), |mapped, chunk| mapped.union(&chunk))
+        })
+    }
+
+    fn map_seeds_serial(&self) -> IntervalSet<u64> {
+        self.map_ranges(self.seeds.iter().copied().collect())
+    }
+
+    fn map_ranges(&self, seeds: IntervalSet<u64>) -> IntervalSet<u64> {
+        self.conversion_stages
+            .iter()
+            .enumerate()
+            .fold(seeds, |seeds, (i, stage)| {
+                let _span = aoc::span_with("stage", || self.category_name(i + 1));
+                Self::map_stage(stage, &seeds)
             })
-            .flatten()
-            .collect()
+    }
+
+    /// Sends every seed through the first map whose source covers it, leaving
+    /// the ones no map covers unchanged.
+    fn map_stage(stage: &[Map], seeds: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let mut unmapped = seeds.clone();
+        let mut mapped = Vec::new();
+
+        for map in stage.iter() {
+            let mut remaining = Vec::new();
+
+            for seed in unmapped.iter() {
+                let maybe_translated = map.translate(seed);
+
+                mapped.extend(maybe_translated.overlap);
+                remaining.extend(maybe_translated.left);
+                remaining.extend(maybe_translated.right);
+            }
+
+            unmapped = remaining.into_iter().collect();
+        }
+
+        unmapped.union(&mapped.into_iter().collect())
+    }
+
+    /// Every value that `stage` sends into `values`, taking into account that
+    /// earlier maps shadow later ones and that uncovered values map to
+    /// themselves.
+    fn unmap_stage(stage: &[Map], values: &IntervalSet<u64>) -> IntervalSet<u64> {
+        let mut covered = IntervalSet::new();
+        let mut sources = IntervalSet::new();
+
+        for map in stage.iter() {
+            let reaching: IntervalSet<u64> =
+                values.iter().filter_map(|v| map.untranslate(v)).collect();
+
+            sources = sources.union(&reaching.difference(&covered));
+            covered.insert(map.source);
+        }
+
+        sources.union(&values.difference(&covered))
+    }
+
+    fn unmap(&self, locations: &IntervalSet<u64>) -> IntervalSet<u64> {
+        self.conversion_stages
+            .iter()
+            .rev()
+            .fold(locations.clone(), |values, stage| {
+                Self::unmap_stage(stage, &values)
+            })
+    }
+
+    /// The seeds of the almanac whose location falls within `locations`.
+    fn seeds_for_locations(&self, locations: Range) -> IntervalSet<u64> {
+        self.unmap(&locations.into())
+            .intersection(&self.seeds.iter().copied().collect())
+    }
+
+    /// The lowest location reached by any seed, along with every seed that
+    /// reaches it.
+    fn closest_seeds(&self) -> Option<(u64, IntervalSet<u64>)> {
+        let location = self
+            .compose()
+            .map_set(&self.seeds.iter().copied().collect())
+            .min()?;
+
+        Some((
+            location,
+            self.seeds_for_locations(Range::new(location, location)?),
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use rstest::rstest;
+    use std::collections::BTreeSet;
 
-    const EXAMPLE: &str = "seeds: 79 14 55 13
+    pub const EXAMPLE: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
 50 98 2
@@ -238,89 +908,521 @@ humidity-to-location map:
         assert_eq!("46", part_2(EXAMPLE));
     }
 
+    #[test]
+    fn composed_test() {
+        assert_eq!("35", part_1_composed(EXAMPLE));
+        assert_eq!("46", part_2_composed(EXAMPLE));
+    }
+
     #[rstest]
     #[case(
-        Range::new(0, 5),
-        Range::new(0, 5),
+        Range::from_len(0, 5).unwrap(),
+        Range::from_len(0, 5).unwrap(),
         MaybeOverlap {
             left: None,
-            overlap: Range::try_from(0, 5),
+            overlap: Range::new(0, 4),
             right: None,
         }
     )]
     #[case(
-        Range::new(1, 3),
-        Range::new(0, 5),
+        Range::from_len(1, 3).unwrap(),
+        Range::from_len(0, 5).unwrap(),
         MaybeOverlap {
-            left: Range::try_from(0, 1),
-            overlap: Range::try_from(1, 4),
-            right: Range::try_from(4, 5),
+            left: Range::new(0, 0),
+            overlap: Range::new(1, 3),
+            right: Range::new(4, 4),
         }
     )]
     #[case(
-        Range::new(0, 5),
-        Range::new(1, 3),
+        Range::from_len(0, 5).unwrap(),
+        Range::from_len(1, 3).unwrap(),
         MaybeOverlap {
             left: None,
-            overlap: Range::try_from(1, 4),
+            overlap: Range::new(1, 3),
             right: None,
         }
     )]
     #[case(
-        Range::new(1, 3),
-        Range::new(5, 5),
+        Range::from_len(1, 3).unwrap(),
+        Range::from_len(5, 5).unwrap(),
         MaybeOverlap {
             left: None,
             overlap: None,
-            right: Range::try_from(5, 10),
+            right: Range::new(5, 9),
         }
     )]
     #[case(
-        Range::new(5, 5),
-        Range::new(1, 3),
+        Range::from_len(5, 5).unwrap(),
+        Range::from_len(1, 3).unwrap(),
         MaybeOverlap {
-            left: Range::try_from(1, 4),
+            left: Range::new(1, 3),
             overlap: None,
             right: None,
         }
     )]
     #[case(
-        Range::new(5, 5),
-        Range::new(10, 3),
+        Range::from_len(5, 5).unwrap(),
+        Range::from_len(10, 3).unwrap(),
         MaybeOverlap {
             left: None,
             overlap: None,
-            right: Range::try_from(10, 13),
+            right: Range::new(10, 12),
         }
     )]
     #[case(
-        Range::new(5, 5),
-        Range::new(9, 4),
+        Range::from_len(5, 5).unwrap(),
+        Range::from_len(9, 4).unwrap(),
         MaybeOverlap {
             left: None,
-            overlap: Range::try_from(9, 10),
-            right: Range::try_from(10, 13),
+            overlap: Range::new(9, 9),
+            right: Range::new(10, 12),
         }
     )]
     #[case(
-        Range::new(0, 5),
-        Range::new(3, 7),
+        Range::from_len(0, 5).unwrap(),
+        Range::from_len(3, 7).unwrap(),
         MaybeOverlap {
             left: None,
-            overlap: Range::try_from(3, 5),
-            right: Range::try_from(5, 10),
+            overlap: Range::new(3, 4),
+            right: Range::new(5, 9),
         }
     )]
     #[case(
-        Range::new(3, 7),
-        Range::new(0, 5),
+        Range::from_len(3, 7).unwrap(),
+        Range::from_len(0, 5).unwrap(),
         MaybeOverlap {
-            left: Range::try_from(0, 3),
-            overlap: Range::try_from(3, 5),
+            left: Range::new(0, 2),
+            overlap: Range::new(3, 4),
             right: None,
         }
     )]
-    fn overlap_test(#[case] lhs: Range, #[case] rhs: Range, #[case] expected: MaybeOverlap) {
+    fn overlap_test(#[case] lhs: Range, #[case] rhs: Range, #[case] expected: MaybeOverlap<u64>) {
         assert_eq!(lhs.test_overlap(&rhs), expected);
     }
+
+    #[rstest]
+    #[case(
+        Map {
+            source: Range::new(u64::MAX - 1, u64::MAX).unwrap(),
+            destination: Range::new(0, 1).unwrap(),
+        },
+        Range::new(u64::MAX - 4, u64::MAX).unwrap(),
+        MaybeOverlap {
+            left: Range::new(u64::MAX - 4, u64::MAX - 2),
+            overlap: Range::new(0, 1),
+            right: None,
+        }
+    )]
+    #[case(
+        Map {
+            source: Range::new(0, 1).unwrap(),
+            destination: Range::new(u64::MAX - 1, u64::MAX).unwrap(),
+        },
+        Range::new(1, 3).unwrap(),
+        MaybeOverlap {
+            left: None,
+            overlap: Range::new(u64::MAX, u64::MAX),
+            right: Range::new(2, 3),
+        }
+    )]
+    fn translate_limits_test(
+        #[case] map: Map,
+        #[case] range: Range,
+        #[case] expected: MaybeOverlap<u64>,
+    ) {
+        assert_eq!(map.translate(&range), expected);
+    }
+
+    const LIMITS: &str = "seeds: 18446744073709551614 2 0 2
+
+seed-to-location map:
+0 18446744073709551615 1
+18446744073709551614 0 2";
+
+    #[test]
+    fn type_limits_test() {
+        assert_eq!(part_1(LIMITS), "2");
+        assert_eq!(part_2(LIMITS), "0");
+        assert_eq!(part_1_composed(LIMITS), "2");
+        assert_eq!(part_2_composed(LIMITS), "0");
+
+        let almanac = Almanac::from_input(LIMITS, Almanac::part_2_seed_parser);
+
+        assert_eq!(
+            Almanac::parse(
+                &format!("{}\n5 18446744073709551615 2", LIMITS),
+                Almanac::part_2_seed_parser
+            ),
+            Err(AlmanacError::Overflow(
+                "5 18446744073709551615 2".to_string()
+            ))
+        );
+        assert_eq!(almanac.compose().lookup(u64::MAX), 0);
+        assert_eq!(
+            almanac
+                .seeds_for_locations(Range::new(u64::MAX - 1, u64::MAX).unwrap())
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect_vec(),
+            [(0, 1), (u64::MAX - 1, u64::MAX - 1)]
+        );
+    }
+
+    #[test]
+    fn compose_test() {
+        let almanac = Almanac::from_input(EXAMPLE, Almanac::part_1_seed_parser);
+        let map = almanac.compose();
+
+        assert_eq!([79, 14, 55, 13].map(|s| map.lookup(s)), [82, 43, 86, 35]);
+        assert_eq!(map.lookup(u64::MAX), u64::MAX);
+        assert!(map
+            .pieces()
+            .windows(2)
+            .all(|w| w[0].source.end() + 1 == w[1].source.start()));
+    }
+
+    #[test]
+    fn profiling_test() {
+        let (answer, spans) = aoc::profiling(|| part_2(EXAMPLE));
+        let stages = spans
+            .iter()
+            .filter(|s| s.name == "stage")
+            .filter_map(|s| s.detail.as_deref())
+            .collect::<HashSet<_>>();
+
+        assert_eq!(answer, "46");
+        assert!(spans.iter().any(|s| s.name == "parse"));
+        assert!(["soil", "water", "location"]
+            .iter()
+            .all(|c| stages.contains(c)));
+        assert!(aoc::chrome_trace(&spans).contains("\"name\":\"stage location\""));
+    }
+
+    #[rstest]
+    #[case(Almanac::part_1_seed_parser, 35, &[(13, 13)])]
+    #[case(Almanac::part_2_seed_parser, 46, &[(82, 82)])]
+    fn closest_seeds_test(
+        #[case] seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>,
+        #[case] expected_location: u64,
+        #[case] expected_seeds: &[(u64, u64)],
+    ) {
+        let (location, seeds) = Almanac::from_input(EXAMPLE, seed_parser)
+            .closest_seeds()
+            .unwrap();
+
+        assert_eq!(location, expected_location);
+        assert_eq!(
+            seeds.iter().map(|r| (r.start(), r.end())).collect_vec(),
+            expected_seeds
+        );
+    }
+
+    #[rstest]
+    #[case(Range::new(0, 45).unwrap(), &[])]
+    #[case(Range::new(46, 60).unwrap(), &[(62, 65), (82, 92)])]
+    #[case(Range::new(0, 100).unwrap(), &[(55, 67), (79, 92)])]
+    fn seeds_for_locations_test(#[case] locations: Range, #[case] expected: &[(u64, u64)]) {
+        let almanac = Almanac::from_input(EXAMPLE, Almanac::part_2_seed_parser);
+
+        assert_eq!(
+            almanac
+                .seeds_for_locations(locations)
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect_vec(),
+            expected
+        );
+    }
+
+    #[test]
+    fn shuffled_stages_test() {
+        let (seeds, stages) = EXAMPLE.split_once("\n\n").unwrap();
+        let shuffled = [seeds]
+            .into_iter()
+            .chain(stages.split("\n\n").collect_vec().into_iter().rev())
+            .join("\n\n");
+
+        assert_eq!(part_1(&shuffled), "35");
+        assert_eq!(part_2(&shuffled), "46");
+        assert_eq!(
+            Almanac::from_input(&shuffled, Almanac::part_1_seed_parser).categories,
+            [
+                "seed",
+                "soil",
+                "fertilizer",
+                "water",
+                "light",
+                "temperature",
+                "humidity",
+                "location"
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(&[("seed", "a"), ("a", "b")], Ok(vec![0, 1]))]
+    #[case(&[("b", "c"), ("seed", "a"), ("a", "b")], Ok(vec![1, 2, 0]))]
+    #[case(&[("seed", "a"), ("seed", "b")], Err(AlmanacError::Branching("seed".to_string())))]
+    #[case(&[("seed", "a"), ("b", "a")], Err(AlmanacError::Merging("a".to_string())))]
+    #[case(&[("seed", "a"), ("b", "c")], Err(AlmanacError::Unreachable("b".to_string())))]
+    #[case(&[("seed", "a"), ("b", "c"), ("c", "b")], Err(AlmanacError::Cycle(vec!["b".to_string(), "c".to_string()])))]
+    #[case(&[("seed", "a"), ("a", "seed")], Err(AlmanacError::Cycle(vec!["seed".to_string(), "a".to_string()])))]
+    #[case(&[], Ok(vec![]))]
+    fn sort_stages_test(
+        #[case] edges: &[(&str, &str)],
+        #[case] expected: Result<Vec<usize>, AlmanacError>,
+    ) {
+        assert_eq!(Almanac::sort_stages(edges), expected);
+    }
+
+    #[rstest]
+    #[case("soil", "fertilizer", &[(81, 81)], Ok(vec![(81, 81)]))]
+    #[case("seed", "location", &[(79, 79), (14, 14)], Ok(vec![(43, 43), (82, 82)]))]
+    #[case("soil", "humidity", &[(81, 81)], Ok(vec![(78, 78)]))]
+    #[case("location", "seed", &[(82, 82)], Ok(vec![(79, 79)]))]
+    #[case("seed", "dirt", &[(1, 1)], Err(AlmanacError::UnknownCategory("dirt".to_string())))]
+    fn convert_test(
+        #[case] from: &str,
+        #[case] to: &str,
+        #[case] values: &[(u64, u64)],
+        #[case] expected: Result<Vec<(u64, u64)>, AlmanacError>,
+    ) {
+        let almanac = Almanac::from_input(EXAMPLE, Almanac::part_1_seed_parser);
+        let values = values
+            .iter()
+            .map(|(s, e)| Range::new(*s, *e).unwrap())
+            .collect();
+
+        assert_eq!(
+            almanac
+                .convert(from, to, &values)
+                .map(|c| c.iter().map(|r| (r.start(), r.end())).collect_vec()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("seeds: 18446744073709551615 2\n\nseed-to-soil map:\n1 2 3", AlmanacError::Overflow("18446744073709551615 2".to_string()))]
+    #[case("seeds: 1 1\n\nseed-to-soil map:\n1 18446744073709551615 2", AlmanacError::Overflow("1 18446744073709551615 2".to_string()))]
+    #[case("seeds: 1 1\n\nseed-to-soil map:\n18446744073709551615 1 2", AlmanacError::Overflow("18446744073709551615 1 2".to_string()))]
+    fn invalid_range_test(#[case] input: &str, #[case] expected: AlmanacError) {
+        assert_eq!(
+            Almanac::parse(input, Almanac::part_2_seed_parser),
+            Err(expected)
+        );
+    }
+
+    #[test]
+    fn zero_length_test() {
+        let almanac = Almanac::from_input(
+            "seeds: 5 0 1 2\n\nseed-to-soil map:\n7 1 0\n10 1 1",
+            Almanac::part_2_seed_parser,
+        );
+
+        assert_eq!(almanac.seeds, [Range::new(1, 2).unwrap()]);
+        assert_eq!(almanac.conversion_stages[0].len(), 1);
+        assert_eq!(almanac.map_seeds().min(), Some(2));
+    }
+
+    #[test]
+    fn malformed_header_test() {
+        assert_eq!(
+            Almanac::parse(
+                "seeds: 1\n\nseed to soil:\n1 2 3",
+                Almanac::part_1_seed_parser
+            ),
+            Err(AlmanacError::MalformedHeader("seed to soil:".to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case(Almanac::part_1_seed_parser, Almanac::part_1_seed_writer)]
+    #[case(Almanac::part_2_seed_parser, Almanac::part_2_seed_writer)]
+    fn write_round_trip_test(
+        #[case] seed_parser: fn(&str) -> Result<Vec<Range>, AlmanacError>,
+        #[case] seed_writer: fn(&[Range]) -> String,
+    ) {
+        for input in [
+            EXAMPLE.to_string(),
+            aoc::read_input("day05.txt"),
+            "seeds: 79 14 55 13".to_string(),
+        ] {
+            let almanac = Almanac::from_input(&input, seed_parser);
+            let normalized = almanac.normalize();
+
+            assert_eq!(
+                Almanac::from_input(&almanac.write(seed_writer), seed_parser),
+                almanac
+            );
+            assert_eq!(
+                Almanac::from_input(&normalized.write(seed_writer), seed_parser),
+                normalized
+            );
+        }
+
+        assert_eq!(
+            Almanac::from_input(EXAMPLE, seed_parser).write(seed_writer),
+            EXAMPLE.to_string() + "\n"
+        );
+    }
+
+    #[test]
+    fn normalize_test() {
+        let almanac = Almanac::from_input(
+            "seeds: 1
+
+seed-to-soil map:
+30 20 5
+10 10 5
+20 10 10
+35 25 3
+7 7 100",
+            Almanac::part_1_seed_parser,
+        );
+
+        assert_eq!(
+            almanac.normalize().write(Almanac::part_1_seed_writer),
+            "seeds: 1
+
+seed-to-soil map:
+25 15 13
+"
+        );
+    }
+
+    fn generated_almanac() -> impl Strategy<Value = Almanac> {
+        let map = (0u64..100, 0u64..100, 1u64..30).prop_map(|(d, s, l)| Map {
+            source: Range::from_len(s, l).unwrap(),
+            destination: Range::from_len(d, l).unwrap(),
+        });
+        let seed = (0u64..150, 1u64..40).prop_map(|(s, l)| Range::from_len(s, l).unwrap());
+
+        (
+            prop::collection::vec(seed, 1..5),
+            prop::collection::vec(prop::collection::vec(map, 0..5), 0..8),
+        )
+            .prop_map(|(seeds, conversion_stages)| Almanac {
+                seeds,
+                categories: ["seed".to_string()]
+                    .into_iter()
+                    .chain((1..=conversion_stages.len()).map(|i| i.to_string()))
+                    .collect(),
+                conversion_stages,
+            })
+    }
+
+    /// Sends a single seed through the almanac one value at a time, the way
+    /// the puzzle describes it.
+    fn brute_force_location(almanac: &Almanac, seed: u64) -> u64 {
+        almanac.conversion_stages.iter().fold(seed, |value, stage| {
+            stage
+                .iter()
+                .find(|m| m.source.contains(value))
+                .map_or(value, |m| value - m.source.start() + m.destination.start())
+        })
+    }
+
+    fn points(set: &IntervalSet<u64>) -> BTreeSet<u64> {
+        set.iter().flat_map(|r| r.start()..=r.end()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn compose_agrees_with_stages_test(almanac in generated_almanac()) {
+            let map = almanac.compose();
+            let seeds: IntervalSet<u64> = almanac.seeds.iter().copied().collect();
+
+            prop_assert_eq!(map.map_set(&seeds), almanac.map_seeds());
+
+            for seed in seeds.iter().flat_map(|r| r.start()..=r.end()) {
+                let single = Almanac {
+                    seeds: vec![Range::from_len(seed, 1).unwrap()],
+                    categories: Vec::new(),
+                    conversion_stages: Vec::new(),
+                };
+                let expected = almanac
+                    .conversion_stages
+                    .iter()
+                    .fold(single.seeds.iter().copied().collect(), |s, stage| {
+                        Almanac::map_stage(stage, &s)
+                    });
+
+                prop_assert_eq!(Some(map.lookup(seed)), expected.min());
+            }
+        }
+
+        #[test]
+        fn normalize_preserves_mapping_test(almanac in generated_almanac()) {
+            let normalized = almanac.normalize();
+            let written = normalized.write(Almanac::part_2_seed_writer);
+
+            prop_assert_eq!(normalized.compose(), almanac.compose());
+            prop_assert_eq!(normalized.map_seeds(), almanac.map_seeds());
+            prop_assert_eq!(Almanac::parse(&written, Almanac::part_2_seed_parser), Ok(normalized));
+        }
+
+        #[test]
+        fn map_seeds_matches_brute_force_test(almanac in generated_almanac()) {
+            let seeds: IntervalSet<u64> = almanac.seeds.iter().copied().collect();
+            let expected = points(&seeds)
+                .into_iter()
+                .map(|seed| brute_force_location(&almanac, seed))
+                .collect::<BTreeSet<_>>();
+
+            prop_assert_eq!(points(&almanac.map_seeds()), expected.clone());
+            prop_assert_eq!(almanac.map_seeds_in(3), almanac.map_seeds_serial());
+            prop_assert_eq!(almanac.map_seeds_in(0), almanac.map_seeds_serial());
+            prop_assert_eq!(
+                points(&almanac.compose().map_set(&seeds)),
+                expected
+            );
+        }
+
+        #[test]
+        fn translate_matches_brute_force_test(
+            almanac in generated_almanac(),
+            start in 0u64..150,
+            len in 1u64..60,
+        ) {
+            let range = Range::from_len(start, len).unwrap();
+
+            for map in almanac.conversion_stages.iter().flatten() {
+                let MaybeOverlap { left, overlap, right } = map.translate(&range);
+                let single = Almanac {
+                    seeds: Vec::new(),
+                    categories: Vec::new(),
+                    conversion_stages: vec![vec![*map]],
+                };
+                let untouched = [left, right].into_iter().flatten().collect::<IntervalSet<_>>();
+                let moved = overlap.map_or(IntervalSet::new(), IntervalSet::from);
+
+                prop_assert!(left.is_none_or(|l| l.end() < map.source.start()));
+                prop_assert!(right.is_none_or(|r| r.start() > map.source.end()));
+                prop_assert_eq!(
+                    points(&untouched).len() + points(&moved).len(),
+                    len as usize
+                );
+
+                for value in range.start()..=range.end() {
+                    let location = brute_force_location(&single, value);
+
+                    match map.source.contains(value) {
+                        true => prop_assert!(moved.contains(location)),
+                        false => prop_assert!(untouched.contains(value)),
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn unmap_inverts_map_test(almanac in generated_almanac(), start in 0u64..200, len in 1u64..50) {
+            let locations = Range::from_len(start, len).unwrap();
+            let map = almanac.compose();
+            let preimage = almanac.unmap(&locations.into());
+
+            for value in 0..400 {
+                prop_assert_eq!(preimage.contains(value), locations.contains(map.lookup(value)));
+            }
+        }
+    }
 }
@@ -0,0 +1,136 @@
+use super::{Almanac, Map, Range};
+use crate::aoc::Rng;
+use itertools::Itertools;
+use std::time::{Duration, Instant};
+
+const CATEGORIES: [&str; 8] = [
+    "seed",
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+    "location",
+];
+const SPACE: u64 = 1 << 32;
+
+/// Builds an almanac shaped like the real ones, with `seeds` seed ranges and
+/// stages that each cut the value space into `entries` pieces and shuffle
+/// them around.
+pub fn generate(seed: u64, seeds: usize, entries: usize) -> Almanac {
+    let mut rng = Rng::new(seed);
+
+    let seed_ranges = (0..seeds)
+        .filter_map(|_| {
+            let start = rng.below(SPACE);
+            let len = 1 + rng.below(SPACE / seeds.max(1) as u64);
+
+            Range::from_len(start, len)
+        })
+        .collect();
+
+    let conversion_stages = (1..CATEGORIES.len())
+        .map(|_| {
+            let cuts = (1..entries.max(1))
+                .map(|_| rng.below(SPACE))
+                .chain([0, SPACE])
+                .sorted()
+                .dedup()
+                .collect_vec();
+            let sources = cuts
+                .windows(2)
+                .map(|w| Range::new(w[0], w[1] - 1).unwrap())
+                .collect_vec();
+
+            let mut destinations = sources.clone();
+            rng.shuffle(&mut destinations);
+
+            let mut next = 0;
+            let destinations = destinations.into_iter().map(|d| {
+                let start = next;
+                next += d.end() - d.start() + 1;
+
+                start
+            });
+
+            sources
+                .iter()
+                .zip(destinations)
+                .map(|(source, start)| Map {
+                    source: *source,
+                    destination: Range::from_len(start, source.end() - source.start() + 1).unwrap(),
+                })
+                .collect()
+        })
+        .collect();
+
+    Almanac {
+        seeds: seed_ranges,
+        categories: CATEGORIES.map(String::from).to_vec(),
+        conversion_stages,
+    }
+}
+
+/// Times serial and parallel seed mapping over `runs` runs each. Returns
+/// whether both gave the same locations.
+pub fn compare(almanac: &Almanac, runs: usize) -> bool {
+    let time = |f: &dyn Fn() -> _| {
+        let timings = (0..runs.max(1))
+            .map(|_| {
+                let start = Instant::now();
+                let mapped = f();
+
+                (start.elapsed(), mapped)
+            })
+            .collect_vec();
+        let best = timings.iter().map(|(t, _)| *t).min().unwrap();
+
+        (best, timings.into_iter().last().unwrap().1)
+    };
+
+    let (serial, serial_locations) = time(&|| almanac.map_seeds_serial());
+    let (parallel, parallel_locations) = time(&|| almanac.map_seeds());
+
+    println!(
+        "{} seed ranges, {} entries per stage, best of {} runs",
+        almanac.seeds.len(),
+        almanac.conversion_stages.first().map_or(0, Vec::len),
+        runs.max(1)
+    );
+    println!("serial:   {:?}", serial);
+    println!("parallel: {:?}", parallel);
+    println!(
+        "speedup:  {:.2}x",
+        serial.as_secs_f64() / parallel.max(Duration::from_nanos(1)).as_secs_f64()
+    );
+
+    if serial_locations != parallel_locations {
+        println!("serial and parallel mapping disagree");
+        println!("serial:   {}", serial_locations.iter().join(", "));
+        println!("parallel: {}", parallel_locations.iter().join(", "));
+    }
+
+    serial_locations == parallel_locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_test() {
+        let almanac = generate(7, 50, 10);
+
+        assert_eq!(almanac, generate(7, 50, 10));
+        assert_eq!(almanac.seeds.len(), 50);
+        assert_eq!(
+            Almanac::parse(
+                &almanac.write(Almanac::part_2_seed_writer),
+                Almanac::part_2_seed_parser
+            ),
+            Ok(almanac.clone())
+        );
+        assert_eq!(almanac.map_seeds_in(4), almanac.map_seeds_serial());
+    }
+}
@@ -0,0 +1,230 @@
+use super::{Almanac, Map, Range};
+use crate::aoc::escape_xml;
+use itertools::Itertools;
+
+const STAGE_WIDTH: f64 = 160.0;
+const CHART_HEIGHT: f64 = 600.0;
+const MARGIN: f64 = 40.0;
+
+/// A fragment of a seed range going through one stage.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Hop {
+    pub stage: usize,
+    pub from: Range,
+    pub to: Range,
+    /// The 1-based position, within its stage, of the entry that moved the
+    /// fragment, or `None` when no entry covers it and it keeps its values.
+    pub entry: Option<usize>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Trace {
+    pub seed: Range,
+    pub hops: Vec<Hop>,
+}
+
+/// Follows every seed range through the stages, keeping each fragment apart
+/// from the others so that it can be told which seeds it came from.
+pub fn trace(almanac: &Almanac) -> Vec<Trace> {
+    almanac
+        .seeds
+        .iter()
+        .map(|seed| {
+            let mut fragments = vec![*seed];
+            let mut hops = Vec::new();
+
+            for (stage, maps) in almanac.conversion_stages.iter().enumerate() {
+                let stage_hops = fragments
+                    .iter()
+                    .flat_map(|f| trace_stage(stage, maps, f))
+                    .collect_vec();
+
+                fragments = stage_hops.iter().map(|h| h.to).collect();
+                hops.extend(stage_hops);
+            }
+
+            Trace { seed: *seed, hops }
+        })
+        .collect()
+}
+
+/// Splits `range` the way `Almanac::map_stage` does, the first entry covering
+/// a value being the one that moves it.
+fn trace_stage(stage: usize, maps: &[Map], range: &Range) -> Vec<Hop> {
+    let mut unmapped = vec![*range];
+    let mut hops = Vec::new();
+
+    for (i, map) in maps.iter().enumerate() {
+        let mut remaining = Vec::new();
+
+        for fragment in unmapped {
+            let translated = map.translate(&fragment);
+
+            if let (Some(from), Some(to)) = (map.source.intersection(&fragment), translated.overlap)
+            {
+                hops.push(Hop {
+                    stage,
+                    from,
+                    to,
+                    entry: Some(i + 1),
+                });
+            }
+
+            remaining.extend(translated.left);
+            remaining.extend(translated.right);
+        }
+
+        unmapped = remaining;
+    }
+
+    hops.extend(unmapped.into_iter().map(|fragment| Hop {
+        stage,
+        from: fragment,
+        to: fragment,
+        entry: None,
+    }));
+    hops.sort_by_key(|h| h.from);
+    hops
+}
+
+pub fn to_csv(almanac: &Almanac, traces: &[Trace]) -> String {
+    let rows = traces.iter().flat_map(|t| {
+        t.hops.iter().map(|h| {
+            format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                t.seed.start(),
+                t.seed.end(),
+                almanac.categories[h.stage],
+                almanac.categories[h.stage + 1],
+                h.from.start(),
+                h.from.end(),
+                h.to.start(),
+                h.to.end(),
+                h.entry.map_or(String::new(), |e| e.to_string())
+            )
+        })
+    });
+
+    format!(
+        "seed_start,seed_end,source,destination,from_start,from_end,to_start,to_end,entry\n{}",
+        rows.format("")
+    )
+}
+
+/// Draws one column per category with every hop as a band joining the
+/// fragment it takes to the fragment it gives, coloured by seed range.
+/// Bands that no entry moved are drawn fainter.
+pub fn to_svg(almanac: &Almanac, traces: &[Trace]) -> String {
+    let max = traces
+        .iter()
+        .flat_map(|t| t.hops.iter().flat_map(|h| [h.from.end(), h.to.end()]))
+        .chain(traces.iter().map(|t| t.seed.end()))
+        .max()
+        .unwrap_or_default() as f64
+        + 1.0;
+    let x = |column: usize| MARGIN + column as f64 * STAGE_WIDTH;
+    let y = |value: f64| MARGIN + value / max * CHART_HEIGHT;
+
+    let columns = almanac.categories.iter().enumerate().map(|(i, category)| {
+        format!(
+            "<line x1=\"{x}\" y1=\"{top}\" x2=\"{x}\" y2=\"{bottom}\" stroke=\"#808080\"/>\n\
+             <text x=\"{x}\" y=\"{label}\" text-anchor=\"middle\">{}</text>\n",
+            escape_xml(category),
+            x = x(i),
+            top = MARGIN,
+            bottom = MARGIN + CHART_HEIGHT,
+            label = MARGIN / 2.0,
+        )
+    });
+
+    let bands = traces.iter().enumerate().flat_map(|(i, t)| {
+        let hue = i * 360 / traces.len();
+
+        t.hops.iter().map(move |h| {
+            format!(
+                "<polygon points=\"{x0},{} {x1},{} {x1},{} {x0},{}\" \
+                 fill=\"hsl({hue}, 70%, 50%)\" fill-opacity=\"{}\">\
+                 <title>seed {}: {} {} to {} {}{}</title></polygon>\n",
+                y(h.from.start() as f64),
+                y(h.to.start() as f64),
+                y(h.to.end() as f64 + 1.0),
+                y(h.from.end() as f64 + 1.0),
+                match h.entry {
+                    Some(_) => 0.6,
+                    None => 0.25,
+                },
+                t.seed,
+                escape_xml(&almanac.categories[h.stage]),
+                h.from,
+                escape_xml(&almanac.categories[h.stage + 1]),
+                h.to,
+                h.entry
+                    .map_or(String::new(), |e| format!(" via entry {}", e)),
+                x0 = x(h.stage),
+                x1 = x(h.stage + 1),
+            )
+        })
+    });
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+         font-family=\"monospace\" font-size=\"12\">\n{}{}</svg>\n",
+        x(almanac.categories.len().saturating_sub(1)) + MARGIN,
+        CHART_HEIGHT + 2.0 * MARGIN,
+        columns.format(""),
+        bands.format("")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interval::IntervalSet;
+    use crate::tests::EXAMPLE;
+
+    #[test]
+    fn trace_test() {
+        let almanac = Almanac::from_input(EXAMPLE, Almanac::part_1_seed_parser);
+        let traces = trace(&almanac);
+
+        assert_eq!(
+            traces[0]
+                .hops
+                .iter()
+                .map(|h| (h.to.start(), h.entry))
+                .collect_vec(),
+            [
+                (81, Some(2)),
+                (81, None),
+                (81, None),
+                (74, Some(2)),
+                (78, Some(3)),
+                (78, None),
+                (82, Some(1)),
+            ]
+        );
+
+        for seed_parser in [Almanac::part_1_seed_parser, Almanac::part_2_seed_parser] {
+            let almanac = Almanac::from_input(EXAMPLE, seed_parser);
+            let last = almanac.conversion_stages.len() - 1;
+            let outputs: IntervalSet<u64> = trace(&almanac)
+                .iter()
+                .flat_map(|t| t.hops.iter().filter(|h| h.stage == last).map(|h| h.to))
+                .collect();
+
+            assert_eq!(outputs, almanac.map_seeds());
+        }
+    }
+
+    #[test]
+    fn export_test() {
+        let almanac = Almanac::from_input(EXAMPLE, Almanac::part_2_seed_parser);
+        let traces = trace(&almanac);
+        let hops = traces.iter().map(|t| t.hops.len()).sum::<usize>();
+        let csv = to_csv(&almanac, &traces);
+
+        assert_eq!(csv.lines().count(), hops + 1);
+        assert!(csv.contains("\n79,92,seed,soil,79,92,81,94,2\n"));
+        assert_eq!(to_svg(&almanac, &traces).matches("<polygon").count(), hops);
+    }
+}
@@ -0,0 +1,231 @@
+use super::Range;
+use crate::interval::IntervalSet;
+use itertools::Itertools;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Strictness {
+    Strict,
+    Warn,
+}
+
+#[derive(Copy, Clone, PartialEq, PartialOrd, Debug)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Issue {
+    Malformed,
+    ZeroLength,
+    SourceOverflow,
+    DestinationOverflow,
+    /// The entry's source range overlaps the one of the entry on `line`, which
+    /// comes first and wins.
+    Overlap {
+        line: usize,
+        range: Range,
+    },
+    /// Values within the stage that no entry covers and map to themselves.
+    Gap(Range),
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Finding {
+    pub stage: String,
+    pub line: usize,
+    pub issue: Issue,
+}
+
+impl Finding {
+    pub fn severity(&self, strictness: Strictness) -> Severity {
+        match (&self.issue, strictness) {
+            (Issue::Gap(_), _) => Severity::Info,
+            (Issue::Malformed, _) | (_, Strictness::Strict) => Severity::Error,
+            (_, Strictness::Warn) => Severity::Warning,
+        }
+    }
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {} ({}): ", self.line, self.stage)?;
+
+        match &self.issue {
+            Issue::Malformed => write!(f, "expected three numbers"),
+            Issue::ZeroLength => write!(f, "entry has zero length"),
+            Issue::SourceOverflow => write!(f, "source range runs past {}", u64::MAX),
+            Issue::DestinationOverflow => {
+                write!(f, "destination range runs past {}", u64::MAX)
+            }
+            Issue::Overlap { line, range } => {
+                write!(f, "source overlaps line {} on {}", line, range)
+            }
+            Issue::Gap(range) => write!(f, "{} is not covered and maps to itself", range),
+        }
+    }
+}
+
+struct Entry {
+    line: usize,
+    destination: u64,
+    source: u64,
+    len: u64,
+}
+
+/// Checks every map entry of the almanac on its own and against the other
+/// entries of its stage.
+pub fn validate(input: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut stages: Vec<(String, usize, Vec<Entry>)> = Vec::new();
+
+    for (i, line) in input.lines().enumerate().skip(1) {
+        let line_number = i + 1;
+
+        if let Some(header) = line.strip_suffix(" map:") {
+            stages.push((header.to_string(), line_number, Vec::new()));
+            continue;
+        }
+
+        let Some((stage, _, entries)) = stages.last_mut() else {
+            continue;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match line
+            .split_whitespace()
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .as_deref()
+        {
+            Ok(&[destination, source, len]) => entries.push(Entry {
+                line: line_number,
+                destination,
+                source,
+                len,
+            }),
+            _ => findings.push(Finding {
+                stage: stage.clone(),
+                line: line_number,
+                issue: Issue::Malformed,
+            }),
+        }
+    }
+
+    for (stage, header_line, entries) in stages {
+        findings.extend(validate_stage(&stage, header_line, &entries));
+    }
+
+    findings.sort_by_key(|f| f.line);
+    findings
+}
+
+fn validate_stage(stage: &str, header_line: usize, entries: &[Entry]) -> Vec<Finding> {
+    let finding = |line, issue| Finding {
+        stage: stage.to_string(),
+        line,
+        issue,
+    };
+    let mut findings = Vec::new();
+    let mut sources = Vec::new();
+
+    for entry in entries {
+        if entry.len == 0 {
+            findings.push(finding(entry.line, Issue::ZeroLength));
+            continue;
+        }
+
+        if Range::from_len(entry.destination, entry.len).is_none() {
+            findings.push(finding(entry.line, Issue::DestinationOverflow));
+        }
+
+        match Range::from_len(entry.source, entry.len) {
+            Some(source) => sources.push((entry.line, source)),
+            None => findings.push(finding(entry.line, Issue::SourceOverflow)),
+        }
+    }
+
+    let by_start = sources
+        .iter()
+        .sorted_by_key(|(_, s)| (s.start(), s.end()))
+        .collect_vec();
+
+    for (i, (line, source)) in by_start.iter().enumerate() {
+        for (other_line, other) in by_start[i + 1..].iter() {
+            let Some(range) = source.intersection(other) else {
+                break;
+            };
+
+            let (first, second) = match line < other_line {
+                true => (*line, *other_line),
+                false => (*other_line, *line),
+            };
+
+            findings.push(finding(second, Issue::Overlap { line: first, range }));
+        }
+    }
+
+    let covered: IntervalSet<u64> = sources.iter().map(|(_, s)| *s).collect();
+
+    if let (Some(min), Some(max)) = (covered.min(), covered.max()) {
+        findings.extend(
+            covered
+                .complement(Range::new(min, max).unwrap())
+                .into_iter()
+                .map(|gap| finding(header_line, Issue::Gap(gap))),
+        );
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("1 5 5\n2 0 5", vec![])]
+    #[case("1 5 5\n2 0 6", vec![(4, Issue::Overlap { line: 3, range: Range::new(5, 5).unwrap() })])]
+    #[case("1 5 5\n2 20 0", vec![(4, Issue::ZeroLength)])]
+    #[case("1 5 5\n18446744073709551615 10 2", vec![(4, Issue::DestinationOverflow)])]
+    #[case("1 18446744073709551615 2", vec![(3, Issue::SourceOverflow)])]
+    #[case("1 5 5\n2 20 5", vec![(2, Issue::Gap(Range::new(10, 19).unwrap()))])]
+    #[case("1 5 x", vec![(3, Issue::Malformed)])]
+    #[case("1 0 10\n2 2 2\n3 5 1", vec![
+        (4, Issue::Overlap { line: 3, range: Range::new(2, 3).unwrap() }),
+        (5, Issue::Overlap { line: 3, range: Range::new(5, 5).unwrap() }),
+    ])]
+    fn validate_test(#[case] entries: &str, #[case] expected: Vec<(usize, Issue)>) {
+        let input = format!("seeds: 1\nseed-to-soil map:\n{}", entries);
+
+        assert_eq!(
+            validate(&input)
+                .into_iter()
+                .map(|f| (f.line, f.issue))
+                .collect_vec(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(Issue::Overlap { line: 1, range: Range::new(0, 0).unwrap() }, Severity::Error, Severity::Warning)]
+    #[case(Issue::ZeroLength, Severity::Error, Severity::Warning)]
+    #[case(Issue::DestinationOverflow, Severity::Error, Severity::Warning)]
+    #[case(Issue::Malformed, Severity::Error, Severity::Error)]
+    #[case(Issue::Gap(Range::new(0, 0).unwrap()), Severity::Info, Severity::Info)]
+    fn severity_test(#[case] issue: Issue, #[case] strict: Severity, #[case] warn: Severity) {
+        let finding = Finding {
+            stage: "seed-to-soil".to_string(),
+            line: 1,
+            issue,
+        };
+
+        assert_eq!(finding.severity(Strictness::Strict), strict);
+        assert_eq!(finding.severity(Strictness::Warn), warn);
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fmt::Display, ops::Range};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Position {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Position {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    fn offset(&self, row: isize, col: isize) -> Option<Self> {
+        Some(Self {
+            row: self.row.checked_add_signed(row)?,
+            col: self.col.checked_add_signed(col)?,
+        })
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.row + 1, self.col + 1)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from `cells` laid out row by row. Returns `None` when the
+    /// number of cells is not a multiple of `width`.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Option<Self> {
+        match (width, cells.len()) {
+            (0, 0) => Some(Self {
+                width,
+                height: 0,
+                cells,
+            }),
+            (0, _) => None,
+            (_, len) if len % width != 0 => None,
+            (_, len) => Some(Self {
+                width,
+                height: len / width,
+                cells,
+            }),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.row < self.height && position.col < self.width
+    }
+
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[position.row * self.width + position.col])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        match self.contains(position) {
+            true => Some(&mut self.cells[position.row * self.width + position.col]),
+            false => None,
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.height).flat_map(|row| (0..self.width).map(move |col| Position::new(row, col)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, row: usize) -> impl Iterator<Item = (Position, &T)> {
+        self.region(row..row + 1, 0..self.width)
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, col: usize) -> impl Iterator<Item = (Position, &T)> {
+        self.region(0..self.height, col..col + 1)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Iterates over the cells inside `rows` × `cols`, clamped to the grid.
+    pub fn region(
+        &self,
+        rows: Range<usize>,
+        cols: Range<usize>,
+    ) -> impl Iterator<Item = (Position, &T)> {
+        let rows = rows.start.min(self.height)..rows.end.min(self.height);
+        let cols = cols.start.min(self.width)..cols.end.min(self.width);
+
+        rows.flat_map(move |row| cols.clone().map(move |col| Position::new(row, col)))
+            .map(|p| (p, self.get(p).unwrap()))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    const NEIGHBORS_4: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+    const NEIGHBORS_8: [(isize, isize); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+
+    pub fn neighbors_4(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbors(position, &Self::NEIGHBORS_4)
+    }
+
+    pub fn neighbors_8(&self, position: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbors(position, &Self::NEIGHBORS_8)
+    }
+
+    fn neighbors<'a>(
+        &'a self,
+        position: Position,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (Position, &'a T)> {
+        offsets
+            .iter()
+            .filter_map(move |(row, col)| position.offset(*row, *col))
+            .filter_map(|p| Some((p, self.get(p)?)))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    const TEST_GRID: &str = "abc
+def
+ghi";
+
+    fn test_grid() -> Grid<char> {
+        Grid::from_cells(3, TEST_GRID.lines().flat_map(str::chars).collect()).unwrap()
+    }
+
+    #[test]
+    fn from_cells_test() {
+        let grid = test_grid();
+
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.get(Position::new(1, 2)), Some(&'f'));
+        assert_eq!(grid.get(Position::new(3, 0)), None);
+        assert_eq!(grid.get(Position::new(0, 3)), None);
+        assert_eq!(grid.to_string(), TEST_GRID);
+        assert_eq!(Grid::from_cells(3, vec!['a'; 5]), None);
+    }
+
+    #[rstest]
+    #[case(Position::new(1, 1), "bdfh", "abcdfghi")]
+    #[case(Position::new(0, 0), "bd", "bde")]
+    #[case(Position::new(2, 1), "egi", "defgi")]
+    fn neighbors_test(#[case] position: Position, #[case] four: &str, #[case] eight: &str) {
+        let grid = test_grid();
+
+        assert_eq!(
+            grid.neighbors_4(position)
+                .map(|(_, c)| c)
+                .collect::<String>(),
+            four
+        );
+        assert_eq!(
+            grid.neighbors_8(position)
+                .map(|(_, c)| c)
+                .collect::<String>(),
+            eight
+        );
+    }
+
+    #[rstest]
+    #[case(0..2, 1..5, "bcef")]
+    #[case(1..2, 0..3, "def")]
+    #[case(5..7, 0..3, "")]
+    fn region_test(#[case] rows: Range<usize>, #[case] cols: Range<usize>, #[case] expected: &str) {
+        let grid = test_grid();
+
+        assert_eq!(
+            grid.region(rows, cols).map(|(_, c)| c).collect::<String>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn row_and_column_test() {
+        let grid = test_grid();
+
+        assert_eq!(grid.row(1).map(|(_, c)| c).collect::<String>(), "def");
+        assert_eq!(grid.column(2).map(|(_, c)| c).collect::<String>(), "cfi");
+    }
+}
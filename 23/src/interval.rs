@@ -0,0 +1,368 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Add, Sub},
+};
+
+pub trait Integer: Copy + Ord + Debug + Display + Add<Output = Self> + Sub<Output = Self> {
+    const MIN: Self;
+    const MAX: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+                const ONE: Self = 1;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A non-empty run of integers from `start` to `end`, both included, so that
+/// intervals reaching the type's maximum are representable.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct MaybeOverlap<T> {
+    pub left: Option<Interval<T>>,
+    pub overlap: Option<Interval<T>>,
+    pub right: Option<Interval<T>>,
+}
+
+impl<T: Integer> Interval<T> {
+    pub fn new(start: T, end: T) -> Option<Self> {
+        (start <= end).then_some(Self { start, end })
+    }
+
+    /// Builds the interval of `len` integers starting at `start`, or `None`
+    /// when it would be empty or run past the type's maximum.
+    pub fn from_len(start: T, len: T) -> Option<Self> {
+        let last = len.checked_sub(T::ONE)?;
+
+        Self::new(start, start.checked_add(last)?)
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    pub fn intersection(&self, rhs: &Self) -> Option<Self> {
+        Self::new(self.start.max(rhs.start), self.end.min(rhs.end))
+    }
+
+    /// Tells whether both intervals overlap or sit right next to each other.
+    pub fn touches(&self, rhs: &Self) -> bool {
+        let (first, second) = match self.start <= rhs.start {
+            true => (self, rhs),
+            false => (rhs, self),
+        };
+
+        first
+            .end
+            .checked_add(T::ONE)
+            .is_none_or(|next| second.start <= next)
+    }
+
+    /// Splits `rhs` into the parts before, inside and after `self`.
+    pub fn test_overlap(&self, rhs: &Self) -> MaybeOverlap<T> {
+        let left = match self.start.checked_sub(T::ONE) {
+            Some(before) => Self::new(rhs.start, before.min(rhs.end)),
+            None => None,
+        };
+        let right = match self.end.checked_add(T::ONE) {
+            Some(after) => Self::new(after.max(rhs.start), rhs.end),
+            None => None,
+        };
+
+        MaybeOverlap {
+            left,
+            overlap: self.intersection(rhs),
+            right,
+        }
+    }
+}
+
+impl<T: Display> Display for Interval<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {}]", self.start, self.end)
+    }
+}
+
+/// A set of integers stored as sorted, disjoint and non-touching intervals.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Sorts `intervals` and merges the ones that overlap or touch.
+    fn normalize(mut intervals: Vec<Interval<T>>) -> Self {
+        intervals.sort();
+
+        let intervals =
+            intervals
+                .into_iter()
+                .fold(Vec::new(), |mut merged: Vec<Interval<T>>, i| {
+                    match merged.last_mut() {
+                        Some(last) if last.touches(&i) => {
+                            *last = Interval {
+                                start: last.start,
+                                end: last.end.max(i.end),
+                            }
+                        }
+                        _ => merged.push(i),
+                    }
+
+                    merged
+                });
+
+        Self { intervals }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.intervals.last().map(|i| i.end)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.intervals.partition_point(|i| i.end < value);
+
+        self.intervals.get(i).is_some_and(|i| i.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let mut intervals = std::mem::take(&mut self.intervals);
+        intervals.push(interval);
+
+        *self = Self::normalize(intervals);
+    }
+
+    pub fn union(&self, rhs: &Self) -> Self {
+        Self::normalize(self.iter().chain(rhs.iter()).copied().collect())
+    }
+
+    pub fn intersection(&self, rhs: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut intervals = Vec::new();
+
+        while let (Some(a), Some(b)) = (self.intervals.get(i), rhs.intervals.get(j)) {
+            intervals.extend(a.intersection(b));
+
+            match a.end < b.end {
+                true => i += 1,
+                false => j += 1,
+            }
+        }
+
+        Self { intervals }
+    }
+
+    pub fn difference(&self, rhs: &Self) -> Self {
+        self.intersection(&rhs.complement(Interval::new(T::MIN, T::MAX).unwrap()))
+    }
+
+    /// Every integer within `bounds` that is not in the set.
+    pub fn complement(&self, bounds: Interval<T>) -> Self {
+        let mut intervals = Vec::new();
+        let mut next = Some(bounds.start);
+
+        for interval in self.intervals.iter() {
+            let Some(start) = next else { break };
+
+            if let Some(before) = interval.start.checked_sub(T::ONE) {
+                intervals.extend(Interval::new(start, before.min(bounds.end)));
+            }
+
+            next = interval.end.checked_add(T::ONE).map(|n| n.max(start));
+        }
+
+        if let Some(start) = next {
+            intervals.extend(Interval::new(start, bounds.end));
+        }
+
+        Self { intervals }
+    }
+}
+
+impl<T: Integer> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        Self::normalize(iter.into_iter().collect())
+    }
+}
+
+impl<T: Integer> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        Self {
+            intervals: vec![interval],
+        }
+    }
+}
+
+impl<T> IntoIterator for IntervalSet<T> {
+    type Item = Interval<T>;
+    type IntoIter = std::vec::IntoIter<Interval<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    fn set<T: Integer>(intervals: &[(T, T)]) -> IntervalSet<T> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end).unwrap())
+            .collect()
+    }
+
+    fn pairs<T: Integer>(set: &IntervalSet<T>) -> Vec<(T, T)> {
+        set.iter().map(|i| (i.start(), i.end())).collect()
+    }
+
+    #[rstest]
+    #[case(&[(5, 9), (0, 2), (3, 3)], &[(0, 3), (5, 9)])]
+    #[case(&[(0, 10), (2, 3), (10, 12)], &[(0, 12)])]
+    #[case(&[(4, 4), (6, 6)], &[(4, 4), (6, 6)])]
+    #[case(&[], &[])]
+    fn normalize_test(#[case] input: &[(u32, u32)], #[case] expected: &[(u32, u32)]) {
+        assert_eq!(pairs(&set(input)), expected);
+    }
+
+    #[rstest]
+    #[case(&[(0, 5), (10, 15)], &[(3, 12)], &[(0, 15)], &[(3, 5), (10, 12)], &[(0, 2), (13, 15)])]
+    #[case(&[(0, 5)], &[(6, 9)], &[(0, 9)], &[], &[(0, 5)])]
+    #[case(&[(0, 5)], &[], &[(0, 5)], &[], &[(0, 5)])]
+    #[case(&[(2, 8)], &[(0, 1), (4, 4), (9, 9)], &[(0, 9)], &[(4, 4)], &[(2, 3), (5, 8)])]
+    fn set_operations_test(
+        #[case] lhs: &[(i64, i64)],
+        #[case] rhs: &[(i64, i64)],
+        #[case] union: &[(i64, i64)],
+        #[case] intersection: &[(i64, i64)],
+        #[case] difference: &[(i64, i64)],
+    ) {
+        let (lhs, rhs) = (set(lhs), set(rhs));
+
+        assert_eq!(pairs(&lhs.union(&rhs)), union);
+        assert_eq!(pairs(&lhs.intersection(&rhs)), intersection);
+        assert_eq!(pairs(&lhs.difference(&rhs)), difference);
+    }
+
+    #[rstest]
+    #[case(&[(2, 3), (6, 7)], (0, 9), &[(0, 1), (4, 5), (8, 9)])]
+    #[case(&[(2, 3), (6, 7)], (3, 6), &[(4, 5)])]
+    #[case(&[], (0, 255), &[(0, 255)])]
+    #[case(&[(0, 255)], (0, 255), &[])]
+    #[case(&[(250, 255)], (0, 255), &[(0, 249)])]
+    #[case(&[(0, 4)], (0, 255), &[(5, 255)])]
+    fn complement_test(
+        #[case] input: &[(u8, u8)],
+        #[case] bounds: (u8, u8),
+        #[case] expected: &[(u8, u8)],
+    ) {
+        assert_eq!(
+            pairs(&set(input).complement(Interval::new(bounds.0, bounds.1).unwrap())),
+            expected
+        );
+    }
+
+    #[test]
+    fn type_limits_test() {
+        let full = set(&[(u64::MAX - 1, u64::MAX), (0, u64::MAX - 2)]);
+
+        assert_eq!(pairs(&full), [(0, u64::MAX)]);
+        assert!(full.contains(u64::MAX));
+        assert_eq!(
+            Interval::from_len(u64::MAX, 1u64).map(|i| i.end()),
+            Some(u64::MAX)
+        );
+        assert_eq!(Interval::from_len(u64::MAX, 2u64), None);
+        assert_eq!(Interval::from_len(3u64, 0), None);
+        assert_eq!(
+            pairs(&set(&[(i8::MIN, -1), (0, i8::MAX)])),
+            [(i8::MIN, i8::MAX)]
+        );
+    }
+
+    #[test]
+    fn contains_test() {
+        let set = set(&[(0u16, 2), (5, 7)]);
+
+        assert_eq!(
+            (0..9).filter(|v| set.contains(*v)).collect::<Vec<u16>>(),
+            [0, 1, 2, 5, 6, 7]
+        );
+    }
+
+    fn interval() -> impl Strategy<Value = Interval<u8>> {
+        (any::<u8>(), any::<u8>()).prop_map(|(a, b)| Interval::new(a.min(b), a.max(b)).unwrap())
+    }
+
+    proptest! {
+        #[test]
+        fn test_overlap_invariants_test(lhs in interval(), rhs in interval()) {
+            let MaybeOverlap { left, overlap, right } = lhs.test_overlap(&rhs);
+            let pieces = [left, overlap, right].into_iter().flatten().collect::<Vec<_>>();
+
+            for value in u8::MIN..=u8::MAX {
+                let holders = pieces.iter().filter(|p| p.contains(value)).count();
+
+                prop_assert_eq!(holders, rhs.contains(value) as usize);
+                prop_assert_eq!(left.is_some_and(|l| l.contains(value)), rhs.contains(value) && value < lhs.start());
+                prop_assert_eq!(overlap.is_some_and(|o| o.contains(value)), rhs.contains(value) && lhs.contains(value));
+                prop_assert_eq!(right.is_some_and(|r| r.contains(value)), rhs.contains(value) && value > lhs.end());
+            }
+
+            prop_assert!(pieces.windows(2).all(|w| w[0].end() < w[1].start()));
+        }
+    }
+}